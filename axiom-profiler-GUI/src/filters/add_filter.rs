@@ -1,5 +1,5 @@
 use material_yew::icon::MatIcon;
-use smt_log_parser::items::{InstIdx, QuantIdx};
+use smt_log_parser::{items::{InstIdx, QuantIdx}, parsers::z3::cost::CostKind};
 use yew::{function_component, html, Callback, Html, MouseEvent, Properties};
 
 use crate::{results::{filters::graph_filters::Filter, svg_result::DEFAULT_NODE_COUNT}, RcParser};
@@ -44,6 +44,9 @@ pub fn AddFilterSidebar(props: &AddFilterSidebarProps) -> Html {
                 .map(|(_, q)| Filter::IgnoreAllButQuantifier(q)).collect(),
             props.insts.clone().into_iter()
                 .map(|(i, _)| Filter::ShowLongestPath(i)).collect(),
+            props.insts.iter()
+                .filter_map(|&(i, _)| graph.orig_graph[petgraph::graph::NodeIndex::from(i)].check)
+                .map(Filter::IgnoreAllButCheck).collect(),
         ]
     } else {
         let mut mls = Vec::new();
@@ -55,6 +58,8 @@ pub fn AddFilterSidebar(props: &AddFilterSidebarProps) -> Html {
         vec![
             vec![Filter::MaxNodeIdx(1000)],
             vec![Filter::IgnoreTheorySolving],
+            vec![Filter::IgnoreWastedInsts],
+            vec![Filter::CostModel(CostKind::default())],
             vec![Filter::MaxInsts(DEFAULT_NODE_COUNT)],
             vec![Filter::MaxBranching(DEFAULT_NODE_COUNT)],
             vec![Filter::MaxDepth(6)],
@@ -1,6 +1,6 @@
 use gloo::timers::callback::Timeout;
 use material_yew::icon::MatIcon;
-use smt_log_parser::items::{InstIdx, QuantIdx};
+use smt_log_parser::{items::{CheckIdx, InstIdx, QuantIdx}, parsers::z3::cost::CostKind};
 use web_sys::{Element, HtmlElement, HtmlInputElement};
 use yew::{function_component, html, Callback, Component, Context, Html, NodeRef, Properties};
 
@@ -336,7 +336,7 @@ pub fn ExistingFilter(props: &ExistingFilterProps) -> Html {
 impl Filter {
     pub fn is_editable(&self) -> bool {
         match self {
-            Filter::IgnoreTheorySolving | Filter::ShowMatchingLoopSubgraph | Filter::IgnoreQuantifier(None) | Filter::IgnoreAllButQuantifier(None) => false,
+            Filter::IgnoreTheorySolving | Filter::ShowMatchingLoopSubgraph | Filter::IgnoreQuantifier(None) | Filter::IgnoreAllButQuantifier(None) | Filter::IgnoreWastedInsts => false,
             _ => true,
         }
     }
@@ -356,6 +356,9 @@ impl Filter {
             Filter::ShowNamedQuantifier(_) => Filter::ShowNamedQuantifier(new_strings[0].clone()),
             Filter::SelectNthMatchingLoop(_) => Filter::SelectNthMatchingLoop(new_data[0].max(1) - 1),
             Filter::ShowMatchingLoopSubgraph => Filter::ShowMatchingLoopSubgraph,
+            Filter::IgnoreAllButCheck(_) => Filter::IgnoreAllButCheck(CheckIdx::from(new_data[0])),
+            Filter::IgnoreWastedInsts => Filter::IgnoreWastedInsts,
+            Filter::CostModel(kind) => Filter::CostModel(CostKind::from_name(&new_strings[0]).unwrap_or(*kind)),
         }
     }
 }
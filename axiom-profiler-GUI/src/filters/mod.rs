@@ -5,7 +5,7 @@ use std::fmt::Display;
 
 use material_yew::icon::MatIcon;
 use petgraph::Direction;
-use smt_log_parser::parsers::ParseState;
+use smt_log_parser::parsers::{z3::cost::CostKind, ParseState};
 use yew::{html, Callback, Component, Context, Html, MouseEvent, NodeRef, Properties};
 
 use crate::{filters::{add_filter::AddFilterSidebar, manage_filter::{DraggableList, ExistingFilter}}, infobars::SidebarSectionHeader, results::{filters::{filter_chain::DEFAULT_FILTER_CHAIN, graph_filters::Filter}, svg_result::{Msg as SVGMsg, UserPermission}}, OpenedFileInfo, RcParser, SIZE_NAMES};
@@ -16,6 +16,7 @@ use self::manage_filter::DragState;
 pub struct FiltersInput {
     pub file: OpenedFileInfo,
     pub search_matching_loops: Callback<()>,
+    pub select_session: Callback<usize>,
 }
 
 pub enum Msg {
@@ -161,7 +162,7 @@ impl Component for FiltersState {
         let file = &ctx.props().file;
         let (size, unit) = file_size_display(file.file_size);
         let details = match &file.parser_state {
-            ParseState::Paused(state) => {
+            ParseState::Paused(state) | ParseState::Waiting(state) => {
                 let (parse_size, parse_unit) = file_size_display(state.bytes_read as u64);
                 format!("{} ({parse_size} {parse_unit}/{size} {unit})", file.file_name)
             }
@@ -170,6 +171,16 @@ impl Component for FiltersState {
             ParseState::Error(err) =>
                 format!("{} (error {err:?})", file.file_name),
         };
+        let sessions = crate::session_items(&file.sessions, file.session, &ctx.props().select_session);
+        let parse_errors = {
+            let parser = file.parser.borrow();
+            let diagnostics = parser.diagnostics();
+            (!diagnostics.is_empty()).then(|| {
+                let total = diagnostics.total();
+                let errors = format!("{total} parse error{}", if total == 1 { "" } else { "s" });
+                html! { <li><a draggable="false" class="trace-file-name" title={diagnostics.to_string()}>{errors}</a></li> }
+            })
+        };
 
         // Existing ops
         let elem_hashes: Vec<_> = self.filter_chain.iter().map(Filter::get_hash).collect();
@@ -255,6 +266,8 @@ impl Component for FiltersState {
         <>
             <SidebarSectionHeader header_text="Current Trace" collapsed_text="Actions on the current trace"><ul>
                 <li><a draggable="false" class="trace-file-name">{details}</a></li>
+                {parse_errors}
+                {sessions}
                 <AddFilterSidebar new_filter={new_filter} found_mls={found_mls} insts={Vec::new()}/>
                 {matching_loops}
                 <li><a draggable="false" href="#" onclick={reset}><div class="material-icons"><MatIcon>{"restore"}</MatIcon></div>{"Reset operations"}</a></li>
@@ -297,6 +310,9 @@ impl Filter {
             Filter::ShowNamedQuantifier(_) => "fingerprint",
             Filter::SelectNthMatchingLoop(_) => "repeat_one",
             Filter::ShowMatchingLoopSubgraph => "repeat",
+            Filter::IgnoreAllButCheck(_) => "fact_check",
+            Filter::IgnoreWastedInsts => "delete_sweep",
+            Filter::CostModel(_) => "functions",
         }
     }
     pub fn short_text(&self) -> String {
@@ -351,6 +367,11 @@ impl Filter {
             Self::ShowMatchingLoopSubgraph => {
                 format!("S only likely matching loops")
             }
+            Self::IgnoreAllButCheck(check) => {
+                format!("Hide all but check |{check}|")
+            }
+            Self::IgnoreWastedInsts => format!("Hide wasted"),
+            Self::CostModel(kind) => format!("Cost by \"{kind}\""),
         }
     }
     pub fn long_text(&self, applied: bool) -> String {
@@ -407,6 +428,14 @@ impl Filter {
             Self::ShowMatchingLoopSubgraph => {
                 format!("{show} only nodes in any potential matching loop")
             }
+            Self::IgnoreAllButCheck(check) => {
+                format!("{hide} all nodes not instantiated during check {}", display(check, applied))
+            }
+            Self::IgnoreWastedInsts => format!("{hide} all nodes not used by the final unsat proof"),
+            Self::CostModel(kind) => {
+                let models = CostKind::ALL.map(|kind| kind.to_string()).join(", ");
+                format!("Rank instantiation costs by \"{}\" (one of {models})", display(kind, applied))
+            }
         }
     }
 }
@@ -4,11 +4,14 @@ use std::sync::{Mutex, OnceLock, RwLock};
 
 use gloo_file::File;
 use gloo_file::{callbacks::FileReader, FileList};
+use results::log_diff::LogDiffView;
 use results::svg_result::{Msg as SVGMsg, RenderingState, SVGResult};
 use smt_log_parser::items::{InstIdx, QuantIdx};
+use smt_log_parser::parsers::z3::diff::LogDiff;
 use smt_log_parser::parsers::z3::inst_graph::InstGraph;
 use smt_log_parser::parsers::z3::z3parser::Z3Parser;
-use smt_log_parser::parsers::{AsyncBufferRead, LogParser, ParseState, ReaderState};
+use smt_log_parser::parsers::{AsyncBufferRead, AsyncParser, LogParser, ParseState, ReaderState};
+use smt_log_parser::Cvc5Parser;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use wasm_streams::ReadableStream;
@@ -30,6 +33,9 @@ mod infobars;
 mod filters;
 mod global_callbacks;
 
+/// Files with this extension are read as cvc5 instantiation traces instead
+/// of Z3 logs.
+const CVC5_EXTENSION: &str = ".cvc5";
 const SIZE_NAMES: [&'static str; 5] = ["B", "KB", "MB", "GB", "TB"];
 
 pub static MOUSE_POSITION: OnceLock<RwLock<PagePosition>> = OnceLock::new();
@@ -50,6 +56,12 @@ pub enum Msg {
     LoadingState(LoadingState),
     SelectedInsts(Vec<(InstIdx, Option<QuantIdx>)>),
     SearchMatchingLoops,
+    SelectSession(usize),
+    /// Select a second trace to compare the opened one with.
+    CompareFile(Option<File>),
+    LoadedCompare(String, u64, Z3Parser, ParseState, bool),
+    SelectCompareSession(usize),
+    CloseCompare,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -107,6 +119,9 @@ pub struct OpenedFileInfo {
     file_name: String,
     file_size: u64,
     parser: RcParser,
+    /// All solver runs found in the file, `parser` is the selected one.
+    sessions: Vec<RcParser>,
+    session: usize,
     parser_state: ParseState,
     parser_cancelled: bool,
     update: Rc<RefCell<Result<Callback<SVGMsg>, Vec<SVGMsg>>>>,
@@ -118,6 +133,7 @@ impl PartialEq for OpenedFileInfo {
         self.file_name == other.file_name
             && self.file_size == other.file_size
             && self.parser == other.parser
+            && self.session == other.session
             && std::mem::discriminant(&self.parser_state) == std::mem::discriminant(&other.parser_state)
             && self.selected_insts == other.selected_insts
     }
@@ -140,16 +156,140 @@ impl OpenedFileInfo {
     }
 }
 
+/// A second trace, compared with the opened one.
+pub struct ComparedFile {
+    file_name: String,
+    /// All solver runs found in the file, the one at `session` is compared.
+    sessions: Vec<RcParser>,
+    session: usize,
+    parser_state: ParseState,
+    parser_cancelled: bool,
+    /// Computed whenever either of the compared sessions changes, rather
+    /// than when rendering.
+    diff: Rc<LogDiff>,
+}
+
+impl ComparedFile {
+    fn compute_diff(old: &RcParser, new: &RcParser) -> Rc<LogDiff> {
+        let (old, new) = (old.borrow(), new.borrow());
+        // Separate graphs, as counting matching loops changes the visibility
+        // of nodes and the displayed graph should not be affected.
+        let mut old_graph = InstGraph::from(&*old);
+        let mut new_graph = InstGraph::from(&*new);
+        Rc::new(LogDiff::new(&old, &mut old_graph, &new, &mut new_graph))
+    }
+
+    fn update_diff(&mut self, old: &RcParser) {
+        self.diff = Self::compute_diff(old, &self.sessions[self.session]);
+    }
+}
+
+/// A note shown with the comparison if a trace was not parsed completely.
+fn parse_note(file_name: &str, state: &ParseState, cancelled: bool) -> Option<String> {
+    match state {
+        ParseState::Error(err) => Some(format!("Parsing {file_name} failed ({err:?}), only the part before the error is compared.")),
+        _ if cancelled => Some(format!("Parsing {file_name} was cancelled, only the part read so far is compared.")),
+        _ if state.is_timeout() => Some(format!("Parsing {file_name} stopped early, only the part read so far is compared.")),
+        _ => None,
+    }
+}
+
+/// Links to select one of the sessions of a trace, empty if there is only
+/// one.
+pub(crate) fn session_items(sessions: &[RcParser], selected: usize, select: &Callback<usize>) -> Option<Html> {
+    (sessions.len() > 1).then(|| {
+        sessions.iter().enumerate().map(|(idx, session)| {
+            let select = select.clone();
+            let onclick = Callback::from(move |e: MouseEvent| {
+                e.prevent_default();
+                select.emit(idx);
+            });
+            let icon = if idx == selected { "radio_button_checked" } else { "radio_button_unchecked" };
+            let version = session.borrow().version_info().map(|v| format!(" ({} {})", v.solver(), v.version())).unwrap_or_default();
+            html! {
+                <li><a draggable="false" href="#" onclick={onclick}><div class="material-icons"><MatIcon>{icon}</MatIcon></div>{format!("Session {}{version}", idx + 1)}</a></li>
+            }
+        }).collect::<Html>()
+    })
+}
+
 pub struct FileDataComponent {
     file_select: NodeRef,
+    compare_select: NodeRef,
     file: Option<OpenedFileInfo>,
+    compare: Option<ComparedFile>,
     reader: Option<FileReader>,
+    /// The compared trace is read independently of the opened one.
+    compare_reader: Option<FileReader>,
     pending_ops: usize,
     progress: LoadingState,
     cancel: Rc<RefCell<bool>>,
+    compare_cancel: Rc<RefCell<bool>>,
     callback_refs: [CallbackRef; 2],
 }
 
+/// The message sent with the parser once a file has been parsed, either
+/// [`Msg::LoadedFile`] or [`Msg::LoadedCompare`].
+type LoadedMsg = fn(String, u64, Z3Parser, ParseState, bool) -> Msg;
+
+/// Parse a file while it is being streamed in, the parser is sent to the
+/// component once done.
+fn parse_stream<P: LogParser + Into<Z3Parser> + 'static>(
+    mut parser: AsyncParser<'static, P>,
+    link: yew::html::Scope<FileDataComponent>,
+    file_name: String,
+    file_size: u64,
+    cancel: Rc<RefCell<bool>>,
+    cancel_cb: Callback<()>,
+    loaded: LoadedMsg,
+) {
+    wasm_bindgen_futures::spawn_local(async move {
+        log::info!("Parsing \"{file_name}\"");
+        let finished = parser.process_until(|_, state| {
+            if state.lines_read % 100_000 == 0 {
+                let parsing = ParseProgress::new(state, file_size);
+                link.send_message(Msg::LoadingState(LoadingState::Parsing(parsing, cancel_cb.clone())));
+            }
+            !*cancel.borrow() && state.bytes_read <= 1024 * 1024 * 1024
+        }).await;
+        if finished.is_timeout() && !*cancel.borrow() {
+            // TODO: make this clear in the UI
+            log::info!("Stopped parsing at 1GB");
+        }
+        let cancel = *cancel.borrow();
+        link.send_message(Msg::LoadingState(LoadingState::DoneParsing(finished.is_timeout(), cancel)));
+        link.send_message(loaded(file_name, file_size, parser.take_parser().into(), finished, cancel))
+    });
+}
+
+/// Parse a file which has been read into memory, used when the browser does
+/// not support streaming.
+fn parse_str<P: LogParser + Into<Z3Parser>>(
+    text_data: &str,
+    link: yew::html::Scope<FileDataComponent>,
+    file_name: String,
+    file_size: u64,
+    cancel: Rc<RefCell<bool>>,
+    cancel_cb: Callback<()>,
+    loaded: LoadedMsg,
+) {
+    let mut parser = P::from_str(text_data);
+    let finished = parser.process_until(|_, state| {
+        if state.lines_read % 100_000 == 0 {
+            let parsing = ParseProgress::new(state, file_size);
+            link.send_message(Msg::LoadingState(LoadingState::Parsing(parsing, cancel_cb.clone())));
+        }
+        !*cancel.borrow() && state.bytes_read <= 512 * 1024 * 1024
+    });
+    if finished.is_timeout() && !*cancel.borrow() {
+        // TODO: make this clear in the UI
+        log::info!("Stopped parsing at 0.5GB (use Chrome or Firefox to increase this limit to 1GB)");
+    }
+    let cancel = *cancel.borrow();
+    link.send_message(Msg::LoadingState(LoadingState::DoneParsing(finished.is_timeout(), cancel)));
+    link.send_message(loaded(file_name, file_size, parser.take_parser().into(), finished, cancel))
+}
+
 impl Component for FileDataComponent {
     type Message = Msg;
     type Properties = ();
@@ -169,11 +309,15 @@ impl Component for FileDataComponent {
         let callback_refs = [mouse_move_ref, drag_over_ref];
         Self {
             file_select: NodeRef::default(),
+            compare_select: NodeRef::default(),
             file: None,
+            compare: None,
             reader: None,
+            compare_reader: None,
             pending_ops: 0,
             progress: LoadingState::NoFileSelected,
             cancel: Rc::default(),
+            compare_cancel: Rc::default(),
             callback_refs,
         }
     }
@@ -191,71 +335,12 @@ impl Component for FileDataComponent {
                 };
                 let changed = self.file.is_some() || self.reader.is_some();
                 drop(self.file.take());
+                drop(self.compare.take());
                 drop(self.reader.take());
+                self.stop_compare();
 
-                let file_name = file.name();
-                let file_size = file.size();
-                log::info!("Selected file \"{file_name}\"");
-                *self.cancel.borrow_mut() = false;
-                let cancel = self.cancel.clone();
-                let cancel_cb = Callback::from(move |_| {
-                    *cancel.borrow_mut() = true;
-                });
-                let cancel = self.cancel.clone();
-                // Turn into stream
-                let blob: &web_sys::Blob = file.as_ref();
-                let stream = ReadableStream::from_raw(blob.stream().unchecked_into());
-                match stream.try_into_async_read() {
-                    Ok(stream) => {
-                        let link = ctx.link().clone();
-                        link.send_message(Msg::LoadingState(LoadingState::StartParsing));
-                        let mut parser = Z3Parser::from_async(stream.buffer());
-                        wasm_bindgen_futures::spawn_local(async move {
-                            log::info!("Parsing \"{file_name}\"");
-                            let finished = parser.process_until(|_, state| {
-                                if state.lines_read % 100_000 == 0 {
-                                    let parsing = ParseProgress::new(state, file_size);
-                                    link.send_message(Msg::LoadingState(LoadingState::Parsing(parsing, cancel_cb.clone())));
-                                }
-                                !*cancel.borrow() && state.bytes_read <= 1024 * 1024 * 1024
-                            }).await;
-                            if finished.is_timeout() && !*cancel.borrow() {
-                                // TODO: make this clear in the UI
-                                log::info!("Stopped parsing at 1GB");
-                            }
-                            let cancel = *cancel.borrow();
-                            link.send_message(Msg::LoadingState(LoadingState::DoneParsing(finished.is_timeout(), cancel)));
-                            link.send_message(Msg::LoadedFile(file_name, file_size, parser.take_parser(), finished, cancel))
-                        });
-                    }
-                    Err((_err, _stream)) => {
-                        let link = ctx.link().clone();
-                        link.send_message(Msg::LoadingState(LoadingState::ReadingToString));
-                        let reader = gloo_file::callbacks::read_as_bytes(&file, move |res| {
-                            log::info!("Loading to string \"{file_name}\"");
-                            let text_data =
-                                String::from_utf8(res.expect("failed to read file")).unwrap();
-                            log::info!("Parsing \"{file_name}\"");
-                            link.send_message(Msg::LoadingState(LoadingState::StartParsing));
-                            let mut parser = Z3Parser::from_str(&text_data);
-                            let finished = parser.process_until(|_, state| {
-                                if state.lines_read % 100_000 == 0 {
-                                    let parsing = ParseProgress::new(state, file_size);
-                                    link.send_message(Msg::LoadingState(LoadingState::Parsing(parsing, cancel_cb.clone())));
-                                }
-                                !*cancel.borrow() && state.bytes_read <= 512 * 1024 * 1024
-                            });
-                            if finished.is_timeout() && !*cancel.borrow() {
-                                // TODO: make this clear in the UI
-                                log::info!("Stopped parsing at 0.5GB (use Chrome or Firefox to increase this limit to 1GB)");
-                            }
-                            let cancel = *cancel.borrow();
-                            link.send_message(Msg::LoadingState(LoadingState::DoneParsing(finished.is_timeout(), cancel)));
-                            link.send_message(Msg::LoadedFile(file_name, file_size, parser.take_parser(), finished, cancel))
-                        });
-                        self.reader = Some(reader);
-                    }
-                };
+                log::info!("Selected file \"{}\"", file.name());
+                self.read_file(ctx, file, false);
                 changed
             }
             Msg::LoadingState(mut state) => {
@@ -269,10 +354,16 @@ impl Component for FileDataComponent {
             Msg::LoadedFile(file_name, file_size, parser, parser_state, parser_cancelled) => {
                 log::info!("Processing \"{file_name}\"");
                 drop(self.reader.take());
+                let sessions: Vec<_> = parser.into_sessions().into_iter().map(RcParser::new).collect();
+                if sessions.len() > 1 {
+                    log::info!("Found {} sessions in \"{file_name}\"", sessions.len());
+                }
                 let file = OpenedFileInfo {
                     file_name,
                     file_size,
-                    parser: RcParser::new(parser),
+                    parser: RcParser::clone(&sessions[0]),
+                    sessions,
+                    session: 0,
                     parser_state,
                     parser_cancelled,
                     update: Rc::new(RefCell::new(Err(Vec::new()))),
@@ -298,6 +389,70 @@ impl Component for FileDataComponent {
                 }
                 false
             }
+            Msg::SelectSession(session) => {
+                let Some(file) = &mut self.file else {
+                    return false;
+                };
+                if file.session == session {
+                    return false;
+                }
+                log::info!("Selected session {session}");
+                file.session = session;
+                file.parser = RcParser::clone(&file.sessions[session]);
+                let graph = file.parser.graph.borrow();
+                file.parser.graph_loaded = graph.is_some();
+                file.parser.found_mls = graph.as_ref().and_then(|g| g.found_matching_loops());
+                drop(graph);
+                // The graph and filters of the new session are set up from
+                // scratch, don't send them the updates meant for the old one.
+                file.update = Rc::new(RefCell::new(Err(Vec::new())));
+                file.selected_insts.clear();
+                if let Some(compare) = &mut self.compare {
+                    compare.update_diff(&file.parser);
+                }
+                true
+            }
+            Msg::CompareFile(file) => {
+                let Some(file) = file else {
+                    return false;
+                };
+                drop(self.compare.take());
+                self.stop_compare();
+                log::info!("Selected file \"{}\" to compare with", file.name());
+                self.read_file(ctx, file, true);
+                true
+            }
+            Msg::LoadedCompare(file_name, _file_size, parser, parser_state, parser_cancelled) => {
+                drop(self.compare_reader.take());
+                let Some(file) = &self.file else {
+                    return false;
+                };
+                log::info!("Comparing with \"{file_name}\"");
+                let sessions: Vec<_> = parser.into_sessions().into_iter().map(RcParser::new).collect();
+                if sessions.len() > 1 {
+                    log::info!("Found {} sessions in \"{file_name}\"", sessions.len());
+                }
+                let diff = ComparedFile::compute_diff(&file.parser, &sessions[0]);
+                self.compare = Some(ComparedFile { file_name, sessions, session: 0, parser_state, parser_cancelled, diff });
+                self.progress = LoadingState::FileDisplayed;
+                true
+            }
+            Msg::SelectCompareSession(session) => {
+                let (Some(file), Some(compare)) = (&self.file, &mut self.compare) else {
+                    return false;
+                };
+                if compare.session == session {
+                    return false;
+                }
+                log::info!("Selected session {session} to compare with");
+                compare.session = session;
+                compare.update_diff(&file.parser);
+                true
+            }
+            Msg::CloseCompare => {
+                self.stop_compare();
+                self.compare.take().is_some()
+            }
         }
     }
 
@@ -322,8 +477,9 @@ impl Component for FileDataComponent {
         let current_trace = match &self.file {
             Some(file) => {
                 let search_matching_loops = ctx.link().callback(|_| Msg::SearchMatchingLoops);
+                let select_session = ctx.link().callback(Msg::SelectSession);
                 html!{
-                    <FiltersState file={file.clone()} search_matching_loops={search_matching_loops}/>
+                    <FiltersState key={file.session} file={file.clone()} search_matching_loops={search_matching_loops} select_session={select_session}/>
                 }
             }
             None => html!{},
@@ -337,6 +493,42 @@ impl Component for FileDataComponent {
                 (files.len() == 1).then(|| files[0].clone())
             ))
         });
+        let compare_select_ref = self.compare_select.clone();
+        let on_compare_change = ctx.link().callback(move |_| {
+            let files = compare_select_ref.cast::<HtmlInputElement>().unwrap().files();
+            Msg::CompareFile(files.map(FileList::from).and_then(|files|
+                (files.len() == 1).then(|| files[0].clone())
+            ))
+        });
+        let compare = self.file.as_ref().map(|_| match &self.compare {
+            Some(compare) => {
+                let close = ctx.link().callback(|e: MouseEvent| {
+                    e.prevent_default();
+                    Msg::CloseCompare
+                });
+                let title = format!("Stop comparing with {}", compare.file_name);
+                let select_session = ctx.link().callback(Msg::SelectCompareSession);
+                let sessions = session_items(&compare.sessions, compare.session, &select_session);
+                html! {
+                    <>
+                        <li><a href="#" draggable="false" onclick={close} {title}><div class="material-icons"><MatIcon>{"close"}</MatIcon></div>{"Close comparison"}</a></li>
+                        {sessions}
+                    </>
+                }
+            }
+            None => {
+                let input = self.compare_select.clone();
+                let open = Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    // Click outside of this event handler, see `rendered`.
+                    let input = input.cast::<HtmlInputElement>().unwrap();
+                    gloo_timers::callback::Timeout::new(0, move || input.click()).forget();
+                });
+                html! {
+                    <li><a href="#" draggable="false" onclick={open}><div class="material-icons"><MatIcon>{"compare"}</MatIcon></div>{"Compare with another trace"}</a></li>
+                }
+            }
+        });
         let sidebar_ref = sidebar.clone();
         let open_files = self.file.is_some();
         let hide_sidebar = Callback::from(move |_| {
@@ -359,14 +551,25 @@ impl Component for FileDataComponent {
             let selected_insts_cb = ctx.link().callback(Msg::SelectedInsts);
             Self::view_file(f.clone(), progress, selected_insts_cb)
         });
+        let compare_view = self.file.as_ref().zip(self.compare.as_ref()).map(|(file, compare)| {
+            let notes: Vec<_> = [
+                parse_note(&file.file_name, &file.parser_state, file.parser_cancelled),
+                parse_note(&compare.file_name, &compare.parser_state, compare.parser_cancelled),
+            ].into_iter().flatten().collect();
+            html! {
+                <LogDiffView diff={compare.diff.clone()} old_name={file.file_name.clone()} new_name={compare.file_name.clone()} {notes} />
+            }
+        });
         html! {
 <>
     <nav class="sidebar" ref={sidebar}>
         <header class="stable"><img src="html/logo_side_small.png" class="brand"/><div class="sidebar-button" onclick={hide_sidebar}><MatIconButton icon="menu"></MatIconButton></div></header>
-        <input type="file" ref={&self.file_select} class="trace_file" accept=".log" onchange={on_change} multiple=false/>
+        <input type="file" ref={&self.file_select} class="trace_file" accept=".log,.cvc5" onchange={on_change} multiple=false/>
+        <input type="file" ref={&self.compare_select} class="trace_file" accept=".log,.cvc5" onchange={on_compare_change} multiple=false/>
         <div class="sidebar-scroll"><div class="sidebar-scroll-container">
             <SidebarSectionHeader header_text="Navigation" collapsed_text="Open or record a new trace"><ul>
                 <li><a href="#" draggable="false" id="open_trace_file"><div class="material-icons"><MatIcon>{"folder_open"}</MatIcon></div>{"Open trace file"}</a></li>
+                {compare}
             </ul></SidebarSectionHeader>
             {current_trace}
             <SidebarSectionHeader header_text="Support" collapsed_text="Documentation & Bugs"><ul>
@@ -388,6 +591,7 @@ impl Component for FileDataComponent {
     <div class="alerts"></div>
     <div class="page">
         {page}
+        {compare_view}
     </div>
 
     // Shortcuts dialog
@@ -423,10 +627,68 @@ impl Component for FileDataComponent {
 }
 
 impl FileDataComponent {
+    /// Stop reading and parsing the trace to compare with, if it is still
+    /// being loaded.
+    fn stop_compare(&mut self) {
+        *self.compare_cancel.borrow_mut() = true;
+        drop(self.compare_reader.take());
+    }
+
+    /// Start reading and parsing a file, either the one to open or the one
+    /// to compare it with. [`Msg::LoadedFile`] or [`Msg::LoadedCompare`] is
+    /// sent with the parser once done.
+    fn read_file(&mut self, ctx: &Context<Self>, file: File, compare: bool) {
+        let file_name = file.name();
+        let file_size = file.size();
+        let (loaded, cancel, reader_slot): (LoadedMsg, _, _) = if compare {
+            (Msg::LoadedCompare, &mut self.compare_cancel, &mut self.compare_reader)
+        } else {
+            (Msg::LoadedFile, &mut self.cancel, &mut self.reader)
+        };
+        // A fresh flag, cancelling a previous load must not affect this one.
+        *cancel = Rc::default();
+        let cancel = cancel.clone();
+        let cancel_cb = Callback::from({
+            let cancel = cancel.clone();
+            move |_| *cancel.borrow_mut() = true
+        });
+        // Turn into stream
+        let blob: &web_sys::Blob = file.as_ref();
+        let stream = ReadableStream::from_raw(blob.stream().unchecked_into());
+        match stream.try_into_async_read() {
+            Ok(stream) => {
+                let link = ctx.link().clone();
+                link.send_message(Msg::LoadingState(LoadingState::StartParsing));
+                if file_name.ends_with(CVC5_EXTENSION) {
+                    parse_stream(Cvc5Parser::from_async(stream.buffer()), link, file_name, file_size, cancel, cancel_cb, loaded);
+                } else {
+                    parse_stream(Z3Parser::from_async(stream.buffer()), link, file_name, file_size, cancel, cancel_cb, loaded);
+                }
+            }
+            Err((_err, _stream)) => {
+                let link = ctx.link().clone();
+                link.send_message(Msg::LoadingState(LoadingState::ReadingToString));
+                let reader = gloo_file::callbacks::read_as_bytes(&file, move |res| {
+                    log::info!("Loading to string \"{file_name}\"");
+                    let text_data =
+                        String::from_utf8(res.expect("failed to read file")).unwrap();
+                    log::info!("Parsing \"{file_name}\"");
+                    link.send_message(Msg::LoadingState(LoadingState::StartParsing));
+                    if file_name.ends_with(CVC5_EXTENSION) {
+                        parse_str::<Cvc5Parser>(&text_data, link, file_name, file_size, cancel, cancel_cb, loaded);
+                    } else {
+                        parse_str::<Z3Parser>(&text_data, link, file_name, file_size, cancel, cancel_cb, loaded);
+                    }
+                });
+                *reader_slot = Some(reader);
+            }
+        };
+    }
+
     fn view_file(data: OpenedFileInfo, progress: Callback<Option<RenderingState>>, selected_insts_cb: Callback<Vec<(InstIdx, Option<QuantIdx>)>>) -> Html {
         log::debug!("Viewing file");
         html! {
-            <SVGResult file={data} progress={progress} selected_insts_cb={selected_insts_cb}/>
+            <SVGResult key={data.session} file={data} progress={progress} selected_insts_cb={selected_insts_cb}/>
         }
     }
 }
@@ -3,8 +3,8 @@ use crate::{utils::usize_input::UsizeInput, results::svg_result::DEFAULT_NODE_CO
 use gloo::console::log;
 use petgraph::Direction;
 use smt_log_parser::{
-    items::{InstIdx, QuantIdx},
-    parsers::z3::inst_graph::{InstGraph, InstInfo, NodeData}, Z3Parser,
+    items::{CheckIdx, InstIdx, QuantIdx},
+    parsers::z3::{cost::CostKind, inst_graph::{InstGraph, InstInfo, NodeData}}, Z3Parser,
 };
 use std::fmt::Display;
 use yew::prelude::*;
@@ -25,6 +25,9 @@ pub enum Filter {
     ShowNamedQuantifier(String),
     SelectNthMatchingLoop(usize),
     ShowMatchingLoopSubgraph,
+    IgnoreAllButCheck(CheckIdx),
+    IgnoreWastedInsts,
+    CostModel(CostKind),
 }
 
 impl Display for Filter {
@@ -79,6 +82,11 @@ impl Display for Filter {
             Self::ShowMatchingLoopSubgraph => {
                 write!(f, "Showing all potential matching loops")
             }
+            Self::IgnoreAllButCheck(check) => {
+                write!(f, "Only show instantiations of check {}", check)
+            }
+            Self::IgnoreWastedInsts => write!(f, "Hide instantiations not used by the proof"),
+            Self::CostModel(kind) => write!(f, "Rank instantiation costs by {kind}"),
         }
     }
 }
@@ -106,6 +114,9 @@ impl Filter {
             Filter::ShowNamedQuantifier(name) => graph.show_named_quantifier(name),
             Filter::SelectNthMatchingLoop(n) => return FilterOutput::MatchingLoopGeneralizedTerms(graph.show_nth_matching_loop(n, parser)),
             Filter::ShowMatchingLoopSubgraph => graph.show_matching_loop_subgraph(),
+            Filter::IgnoreAllButCheck(check) => graph.retain_check(check),
+            Filter::IgnoreWastedInsts => graph.retain_useful(),
+            Filter::CostModel(kind) => graph.set_cost_model(parser, kind),
         }
         FilterOutput::None
     }
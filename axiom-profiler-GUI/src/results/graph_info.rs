@@ -10,7 +10,7 @@ use smt_log_parser::{
 use web_sys::HtmlElement;
 use yew::prelude::*;
 
-use super::graph::graph_container::GraphContainer;
+use super::{graph::graph_container::GraphContainer, quant_stats::QuantStatsTable};
 
 pub struct GraphInfo {
     is_expanded_node: IndexMap<InstIdx, bool>,
@@ -243,6 +243,7 @@ impl Component for GraphInfo {
             <li>{term}</li>
         });
         let outdated = ctx.props().outdated.then(|| html! {<div class="outdated"></div>});
+        let cost_model = ctx.props().parser.graph.borrow().as_ref().map(|graph| graph.cost_model()).unwrap_or_default();
         html! {
             <>
             <GraphContainer
@@ -270,6 +271,10 @@ impl Component for GraphInfo {
                 <div>
                     <ul>{for generalized_terms}</ul>
                 </div>
+                <h2>{"Quantifier statistics:"}</h2>
+                <div>
+                    <QuantStatsTable parser={ctx.props().parser.clone()} {cost_model} />
+                </div>
             </div>
             {outdated}
             </>
@@ -307,18 +312,31 @@ fn selected_nodes_info(
                 })
             };
             let z3_gen = selected_inst.z3_gen.map(|gen| format!(", Z3 generation {gen}")).unwrap_or_default();
+            let cost = selected_inst.cost.map(|cost| format!("Calculated {cost}")).unwrap_or_else(|| "Unavailable".to_string());
+            let rewrite = selected_inst.rewrite_of.as_ref().map(|rewrite_of| {
+                let insts = |insts: &Vec<InstIdx>| insts.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+                html! {
+                    <>
+                    <li><h4>{"Rewrite of: "}</h4><p>{rewrite_of}</p></li>
+                    <li><h4>{"Rewrite chain: "}</h4><p>{insts(&selected_inst.rewrite_chain)}</p></li>
+                    <li>{get_ul("Rewritten to: ", &selected_inst.rewrite_results)}</li>
+                    <li><h4>{"Consumed by: "}</h4><p>{insts(&selected_inst.rewrite_consumers)}</p></li>
+                    </>
+                }
+            });
             html! {
             <details id={format!("{}", usize::from(selected_inst.inst_idx))} onclick={on_select}>
                 <summary>{format!("Node {}", usize::from(selected_inst.inst_idx))}</summary>
                 <ul>
                     <li><h4>{"Instantiation number: "}</h4><p>{format!("{}", selected_inst.inst_idx)}</p></li>
-                    <li><h4>{"Cost: "}</h4><p>{"Calculated "}{selected_inst.cost}{z3_gen}</p></li>
+                    <li><h4>{"Cost: "}</h4><p>{cost}{z3_gen}</p></li>
                     <li><h4>{"Instantiated formula: "}</h4><p>{&selected_inst.formula}</p></li>
                     <li>{get_ul("Blamed terms: ", &selected_inst.blamed_terms)}</li>
                     <li>{get_ul("Bound terms: ", &selected_inst.bound_terms)}</li>
                     <li>{get_ul("Yield terms: ", &selected_inst.yields_terms)}</li>
                     <li>{get_ul("Equality explanations: ", &selected_inst.equality_expls)}</li>
                     <li><h4>{"Resulting term: "}</h4><p>{if let Some(ref val) = selected_inst.resulting_term {val.to_string()} else { String::new() }}</p></li>
+                    {for rewrite}
                 </ul>
             </details>
         }})
@@ -362,6 +380,11 @@ fn selected_edges_info(
                         <h4>{"Equality: "}</h4><p>{selected_edge.blame_term.clone()}</p>
                         </div>
                     },
+                    BlameKind::TheoryEquality { .. } => html! {
+                        <div>
+                        <h4>{"Term of derived equality: "}</h4><p>{selected_edge.blame_term.clone()}</p>
+                        </div>
+                    },
                 }}
             </details>
             }
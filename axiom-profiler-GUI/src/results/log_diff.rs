@@ -0,0 +1,104 @@
+use std::rc::Rc;
+
+use smt_log_parser::parsers::z3::diff::{DiffStatus, LogDiff, QuantDiff, QuantSummary};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct LogDiffViewProps {
+    /// Computed by the owner of the traces, which keeps it until either
+    /// of them changes.
+    pub diff: Rc<LogDiff>,
+    pub old_name: String,
+    pub new_name: String,
+    /// Shown above the comparison, e.g. if a trace was not fully parsed.
+    pub notes: Vec<String>,
+}
+
+/// A side-by-side comparison of the quantifier instantiations of two traces,
+/// see [`LogDiff`].
+pub struct LogDiffView;
+
+impl LogDiffView {
+    fn view_side(summary: Option<&QuantSummary>) -> Html {
+        let Some(summary) = summary else {
+            return html! { <><td>{"-"}</td><td/><td/><td/><td/></> };
+        };
+        html! {
+            <>
+                <td title={summary.body.clone()}>{&summary.name}</td>
+                <td>{summary.insts}</td>
+                <td>{format!("{:.1}", summary.cost)}</td>
+                <td>{summary.matching_loops}</td>
+                <td>{summary.max_depth.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string())}</td>
+            </>
+        }
+    }
+
+    fn view_row(diff: &QuantDiff) -> Html {
+        let (status, style) = match diff.status() {
+            DiffStatus::Added => ("Added", "background-color: hsl(120, 60%, 90%)"),
+            DiffStatus::Removed => ("Removed", "background-color: hsl(0, 60%, 90%)"),
+            DiffStatus::Changed => ("Changed", ""),
+            DiffStatus::Unchanged => ("", "color: grey"),
+        };
+        let delta = &diff.delta;
+        html! {
+            <tr {style}>
+                <td>{status}</td>
+                {Self::view_side(diff.old.as_ref())}
+                {Self::view_side(diff.new.as_ref())}
+                <td>{format!("{:+}", delta.insts)}</td>
+                <td>{format!("{:+.1}", delta.cost)}</td>
+                <td>{format!("{:+}", delta.matching_loops)}</td>
+                <td>{format!("{:+}", delta.max_depth)}</td>
+            </tr>
+        }
+    }
+}
+
+impl Component for LogDiffView {
+    type Message = ();
+    type Properties = LogDiffViewProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let count = |status| props.diff.with_status(status).count();
+        let summary = format!(
+            "{} changed, {} added and {} removed quantifiers, those with the largest change in cost first.",
+            count(DiffStatus::Changed),
+            count(DiffStatus::Added),
+            count(DiffStatus::Removed),
+        );
+        let side_headers = || html! {
+            <><th>{"Quantifier"}</th><th>{"Instances"}</th><th>{"Cost"}</th><th>{"Loops"}</th><th>{"Max depth"}</th></>
+        };
+        html! {
+            <div style="position: absolute; inset: 0; overflow: auto; background-color: white; padding: 1em">
+                <h2>{format!("Comparing {} with {}", props.old_name, props.new_name)}</h2>
+                {for props.notes.iter().map(|note| html! { <p style="color: darkred">{note}</p> })}
+                <p>{summary}</p>
+                <table>
+                    <thead>
+                        <tr>
+                            <th/>
+                            <th colspan="5">{&props.old_name}</th>
+                            <th colspan="5">{&props.new_name}</th>
+                            <th colspan="4">{"Change"}</th>
+                        </tr>
+                        <tr>
+                            <th>{"Status"}</th>
+                            {side_headers()}
+                            {side_headers()}
+                            <th>{"Instances"}</th><th>{"Cost"}</th><th>{"Loops"}</th><th>{"Max depth"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>{for props.diff.quantifiers.iter().map(Self::view_row)}</tbody>
+                </table>
+            </div>
+        }
+    }
+}
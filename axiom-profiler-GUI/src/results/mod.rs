@@ -1,5 +1,7 @@
 pub mod filters;
 pub mod graph;
 pub mod graph_info;
+pub mod log_diff;
+pub mod quant_stats;
 pub mod svg_result;
 pub mod worker;
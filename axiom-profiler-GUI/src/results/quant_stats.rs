@@ -0,0 +1,235 @@
+use std::cmp::Ordering;
+
+use smt_log_parser::{
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    items::QuantIdx,
+    parsers::z3::{cost::CostKind, z3parser::QuantStats},
+};
+use yew::prelude::*;
+
+use crate::RcParser;
+
+/// The columns of the quantifier statistics table, the table can be sorted by
+/// any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Matches,
+    Insts,
+    UnusedMatches,
+    InstRatio,
+    Patterns,
+    MaxGeneration,
+    MeanGeneration,
+    Yields,
+    Cost,
+    SelfCost,
+}
+
+impl Column {
+    const ALL: [Self; 11] = [
+        Self::Name,
+        Self::Matches,
+        Self::Insts,
+        Self::UnusedMatches,
+        Self::InstRatio,
+        Self::Patterns,
+        Self::MaxGeneration,
+        Self::MeanGeneration,
+        Self::Yields,
+        Self::Cost,
+        Self::SelfCost,
+    ];
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Name => "Quantifier",
+            Self::Matches => "Matches",
+            Self::Insts => "Instances",
+            Self::UnusedMatches => "Unused",
+            Self::InstRatio => "Inst ratio",
+            Self::Patterns => "Per pattern",
+            Self::MaxGeneration => "Max gen",
+            Self::MeanGeneration => "Mean gen",
+            Self::Yields => "Yields",
+            Self::Cost => "Cost",
+            Self::SelfCost => "Self cost",
+        }
+    }
+    fn description(self) -> &'static str {
+        match self {
+            Self::Name => "The name (qid) of the quantifier",
+            Self::Matches => "All matches found, whether instantiated or not",
+            Self::Insts => "The number of instantiations",
+            Self::UnusedMatches => "Matches which were never instantiated",
+            Self::InstRatio => "The fraction of matches which were instantiated, a low ratio suggests the patterns are too permissive",
+            Self::Patterns => "The instantiated and total matches of each pattern, hover to see the pattern",
+            Self::MaxGeneration => "The highest Z3 generation of any instantiation",
+            Self::MeanGeneration => "The mean Z3 generation of the instantiations",
+            Self::Yields => "The number of enodes created by the instantiations",
+            Self::Cost => "The summed cost of all instantiations, under the selected cost model",
+            Self::SelfCost => "The cost of instantiations which do not depend on another instantiation of the same quantifier",
+        }
+    }
+    /// Most columns are more interesting with the largest values first.
+    fn descending_by_default(self) -> bool {
+        self != Self::Name
+    }
+    fn cmp(self, a: &Row, b: &Row) -> Ordering {
+        let (sa, sb) = (&a.stats, &b.stats);
+        let mean = |s: &QuantStats| s.mean_generation.unwrap_or(f32::NEG_INFINITY);
+        let ratio = |s: &QuantStats| s.inst_ratio().unwrap_or(f32::NEG_INFINITY);
+        let ordering = match self {
+            Self::Name => a.name.cmp(&b.name),
+            Self::Matches => sa.matches.cmp(&sb.matches),
+            Self::Insts => sa.insts.cmp(&sb.insts),
+            Self::UnusedMatches => sa.unused_matches.cmp(&sb.unused_matches),
+            Self::InstRatio => ratio(sa).total_cmp(&ratio(sb)),
+            Self::Patterns => sa.patterns.len().cmp(&sb.patterns.len()),
+            Self::MaxGeneration => sa.max_generation.cmp(&sb.max_generation),
+            Self::MeanGeneration => mean(sa).total_cmp(&mean(sb)),
+            Self::Yields => sa.yields.cmp(&sb.yields),
+            Self::Cost => sa.cost.total_cmp(&sb.cost),
+            Self::SelfCost => sa.self_cost.total_cmp(&sb.self_cost),
+        };
+        ordering.then(a.quant.cmp(&b.quant))
+    }
+}
+
+struct Row {
+    quant: QuantIdx,
+    name: String,
+    patterns: Vec<String>,
+    stats: QuantStats,
+}
+
+impl Row {
+    fn view(&self) -> Html {
+        let stats = &self.stats;
+        let patterns = stats.patterns.iter().zip(&self.patterns).enumerate().map(|(i, (p, pattern))| html! {
+            <>{if i > 0 { ", " } else { "" }}<span title={pattern.clone()}>{format!("{}/{}", p.insts, p.matches)}</span></>
+        });
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        html! {
+            <tr>
+                <td>{&self.name}</td>
+                <td>{stats.matches}</td>
+                <td>{stats.insts}</td>
+                <td>{stats.unused_matches}</td>
+                <td>{or_dash(stats.inst_ratio().map(|r| format!("{:.0}%", r * 100.0)))}</td>
+                <td>{for patterns}</td>
+                <td>{or_dash(stats.max_generation.map(|g| g.to_string()))}</td>
+                <td>{or_dash(stats.mean_generation.map(|g| format!("{g:.2}")))}</td>
+                <td>{stats.yields}</td>
+                <td>{format!("{:.1}", stats.cost)}</td>
+                <td>{format!("{:.1}", stats.self_cost)}</td>
+            </tr>
+        }
+    }
+}
+
+pub enum Msg {
+    SortBy(Column),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct QuantStatsTableProps {
+    pub parser: RcParser,
+    pub cost_model: CostKind,
+}
+
+/// A sortable table of [`QuantStats`] for all quantifiers with at least one
+/// match.
+pub struct QuantStatsTable {
+    rows: Vec<Row>,
+    sort_by: Column,
+    descending: bool,
+}
+
+impl QuantStatsTable {
+    fn compute_rows(props: &QuantStatsTableProps) -> Vec<Row> {
+        let parser = props.parser.borrow();
+        let ctxt = DisplayCtxt {
+            parser: &parser,
+            display_term_ids: false,
+            display_quantifier_name: false,
+            use_mathematical_symbols: true,
+        };
+        let stats = parser.quant_stats(&parser.inst_costs(&props.cost_model));
+        stats
+            .into_iter_enumerated()
+            .filter(|(_, stats)| stats.matches > 0)
+            .map(|(quant, stats)| Row {
+                quant,
+                name: parser.quant_name(quant),
+                patterns: stats.patterns.iter().map(|p| p.pattern.with(&ctxt).to_string()).collect(),
+                stats,
+            })
+            .collect()
+    }
+    fn sort(&mut self) {
+        let (sort_by, descending) = (self.sort_by, self.descending);
+        self.rows.sort_by(|a, b| {
+            let ordering = sort_by.cmp(a, b);
+            if descending { ordering.reverse() } else { ordering }
+        });
+    }
+}
+
+impl Component for QuantStatsTable {
+    type Message = Msg;
+    type Properties = QuantStatsTableProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut table = Self {
+            rows: Self::compute_rows(ctx.props()),
+            sort_by: Column::Cost,
+            descending: true,
+        };
+        table.sort();
+        table
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
+        self.rows = Self::compute_rows(ctx.props());
+        self.sort();
+        true
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::SortBy(column) => {
+                if self.sort_by == column {
+                    self.descending = !self.descending;
+                } else {
+                    self.sort_by = column;
+                    self.descending = column.descending_by_default();
+                }
+                self.sort();
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let headers = Column::ALL.into_iter().map(|column| {
+            let arrow = match (self.sort_by == column, self.descending) {
+                (false, _) => "",
+                (true, true) => " ▼",
+                (true, false) => " ▲",
+            };
+            let onclick = ctx.link().callback(move |_| Msg::SortBy(column));
+            html! {
+                <th title={column.description()} {onclick} style="cursor: pointer; white-space: nowrap">
+                    {column.header()}{arrow}
+                </th>
+            }
+        });
+        html! {
+            <table>
+                <thead><tr>{for headers}</tr></thead>
+                <tbody>{for self.rows.iter().map(Row::view)}</tbody>
+            </table>
+        }
+    }
+}
@@ -13,9 +13,10 @@ use super::{
 use material_yew::WeakComponentLink;
 use num_format::{Locale, ToFormattedString};
 use petgraph::dot::{Config, Dot};
-use petgraph::graph::EdgeIndex;
+use petgraph::graph::{EdgeIndex, NodeIndex};
 use smt_log_parser::{
     items::{BlameKind, InstIdx, MatchKind, QuantIdx},
+    parsers::z3::cost::CostKind,
     parsers::z3::inst_graph::{EdgeInfo, EdgeType, InstGraph, InstInfo, VisibleGraphInfo},
 };
 use std::num::NonZeroUsize;
@@ -26,7 +27,12 @@ use yew::prelude::*;
 pub const EDGE_LIMIT: usize = 2000;
 pub const DEFAULT_NODE_COUNT: usize = 200;
 pub const NODE_COLOUR_SATURATION: f64 = 0.4;
+/// Instantiations which were not used by the final proof are drawn faded.
+pub const WASTED_NODE_COLOUR_SATURATION: f64 = 0.1;
 pub const NODE_COLOUR_VALUE: f64 = 0.95;
+/// The value of the costliest node under the selected cost model, cheaper
+/// nodes are drawn brighter up to [`NODE_COLOUR_VALUE`].
+pub const COSTLY_NODE_COLOUR_VALUE: f64 = 0.7;
 
 pub enum Msg {
     ConstructedGraph(SVGData),
@@ -113,7 +119,12 @@ impl Component for SVGResult {
             let get_node_info = Callback::from({
                 let node_info_map = inst_graph.get_node_info_map();
                 move |(node, ignore_ids, parser): (InstIdx, bool, RcParser)| {
-                    node_info_map.get_instantiation_info(node, &parser.borrow(), ignore_ids)
+                    // The graph is borrowed mutably while it is being
+                    // filtered, the cost is then shown as unavailable.
+                    let cost = parser.graph.try_borrow().ok().and_then(|graph| {
+                        graph.as_ref().map(|graph| graph.orig_graph[NodeIndex::from(node)].cost())
+                    });
+                    node_info_map.get_instantiation_info(node, cost, &parser.borrow(), ignore_ids)
                 }
             });
             let get_edge_info = Callback::from({
@@ -204,6 +215,7 @@ impl Component for SVGResult {
             Msg::ResetGraph => {
                 log::debug!("Resetting graph");
                 inst_graph.reset_visibility_to(true);
+                inst_graph.set_cost_model(&ctx.props().file.parser.borrow(), CostKind::default());
                 false
             }
             Msg::RenderGraph(UserPermission { permission }) => {
@@ -225,6 +237,7 @@ impl Component for SVGResult {
                     log::debug!("Rendering graph");
                     ctx.props().progress.emit(Some(RenderingState::GraphToDot));
                     let filtered_graph = &inst_graph.visible_graph;
+                    let orig_node_count = inst_graph.orig_graph.node_count();
 
                     // Performance observations (default value is in [])
                     //  - splines=false -> 38s | [splines=true] -> ??
@@ -269,6 +282,10 @@ impl Component for SVGResult {
                                         kind: BlameKind::Equality { .. },
                                         ..
                                     } => "empty",
+                                    EdgeType::Direct {
+                                        kind: BlameKind::TheoryEquality { .. },
+                                        ..
+                                    } => "odiamond",
                                     _ => "normal",
                                 }
                             ),
@@ -291,7 +308,13 @@ impl Component for SVGResult {
                                             (true, false) => "invhouse",
                                             (true, true) => "diamond",
                                         },
-                                        self.colour_map.get(&node_data.mkind, NODE_COLOUR_SATURATION),
+                                        self.colour_map.get(&node_data.mkind, match node_data.useful {
+                                            Some(false) => WASTED_NODE_COLOUR_SATURATION,
+                                            _ => NODE_COLOUR_SATURATION,
+                                        }, {
+                                            let rank = node_data.cost_rank() as f64 / orig_node_count.max(1) as f64;
+                                            COSTLY_NODE_COLOUR_VALUE + rank * (NODE_COLOUR_VALUE - COSTLY_NODE_COLOUR_VALUE)
+                                        }),
                                     )
                             },
                         )
@@ -452,7 +475,7 @@ impl QuantIdxToColourMap {
         }
     }
 
-    pub fn get(&self, mkind: &MatchKind, sat: f64) -> HSVColour {
+    pub fn get(&self, mkind: &MatchKind, sat: f64, val: f64) -> HSVColour {
         let qidx = mkind.quant_idx();
         debug_assert!(self.non_quant_insts || qidx.is_some());
         let idx = qidx
@@ -464,7 +487,7 @@ impl QuantIdxToColourMap {
         HSVColour {
             hue: idx_perm as f64 / self.total_count as f64,
             sat,
-            val: NODE_COLOUR_VALUE,
+            val,
         }
     }
 
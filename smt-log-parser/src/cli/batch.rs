@@ -0,0 +1,230 @@
+//! Batch analysis of a single log, configured by a `settings.json` or
+//! `settings.txt` file so that the same analysis can be rerun (e.g. nightly).
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use smt_log_parser::parsers::z3::cost::CostKind;
+
+use super::{
+    commands::{FileStats, Quantifiers, Report, Top},
+    load::{Limits, Loaded, Outcome, SNAPSHOT_EXTENSION},
+    CliResult, QuantOrder, TopOrder,
+};
+
+/// The configuration of a batch run. Limits of `0` mean unlimited.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// The log to analyse, relative to the settings file.
+    pub file: PathBuf,
+    /// How to rank instantiations: `cost`, `branching` or `line_no`.
+    #[serde(default = "Settings::default_sort_by")]
+    pub sort_by: TopOrder,
+    /// How many of the highest ranked instantiations to keep.
+    #[serde(default)]
+    pub number_inst: usize,
+    /// Seconds after which to stop parsing.
+    #[serde(default)]
+    pub timeout: f32,
+    /// Lines after which to stop parsing.
+    #[serde(default)]
+    pub line_limit: usize,
+    /// Also write the per-quantifier statistics and a snapshot of the parsed
+    /// log.
+    #[serde(default)]
+    pub save_all_data: bool,
+    /// Load the snapshot written by a previous run into the same output
+    /// directory, if there is one from the same log and limits, rather than
+    /// parsing the log again.
+    #[serde(default)]
+    pub reuses: bool,
+    /// Report progress on stderr.
+    #[serde(default)]
+    pub verbose: bool,
+}
+
+impl Settings {
+    fn default_sort_by() -> TopOrder {
+        TopOrder::Cost
+    }
+
+    /// Read the settings from a file, which is either JSON or (for any
+    /// extension other than `.json`) the `KEY value` per line format of
+    /// `settings.txt`.
+    pub fn load(path: &Path) -> CliResult<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut settings: Self = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&contents)?
+        } else {
+            serde_json::from_value(Self::parse_txt(&contents)?)?
+        };
+        if settings.file.is_relative() {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            settings.file = dir.join(&settings.file);
+        }
+        Ok(settings)
+    }
+
+    /// Convert the `settings.txt` format into the equivalent JSON object.
+    fn parse_txt(contents: &str) -> CliResult<Value> {
+        let mut settings = Map::new();
+        for line in contents.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let key = match key {
+                "FILE" => "file",
+                "FILTER" => "sort_by",
+                "NUMBER_INST" => "number_inst",
+                "TIMEOUT" => "timeout",
+                "LINE_LIMIT" => "line_limit",
+                "SAVE_ALL_DATA" | "ENABLE_IO" => "save_all_data",
+                "REUSE" => "reuses",
+                "VERBOSE" => "verbose",
+                _ => return Err(format!("unknown setting `{key}`").into()),
+            };
+            let value = value.trim();
+            // Everything but the file name and ordering is a bool or number.
+            let value = match key {
+                "file" | "sort_by" => Value::String(value.to_string()),
+                _ => serde_json::from_str(value).map_err(|_| format!("invalid value `{value}` for `{key}`"))?,
+            };
+            settings.insert(key.to_string(), value);
+        }
+        Ok(Value::Object(settings))
+    }
+
+    fn limits(&self, memory: Option<u64>) -> Limits {
+        Limits {
+            // Zero, negative or invalid timeouts mean no timeout.
+            timeout: Duration::try_from_secs_f32(self.timeout).ok().filter(|timeout| !timeout.is_zero()).unwrap_or(Duration::MAX),
+            memory,
+            lines: (self.line_limit != 0).then_some(self.line_limit),
+        }
+    }
+}
+
+/// What the snapshot of a batch run was parsed from, written next to it. The
+/// snapshot is only reused by later runs which would parse the same log with
+/// the same limits.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SnapshotSource {
+    file: PathBuf,
+    timeout: f32,
+    line_limit: usize,
+    memory: Option<u64>,
+    /// Why parsing stopped when the snapshot was written.
+    outcome: Outcome,
+}
+
+impl SnapshotSource {
+    const FILE_NAME: &'static str = "snapshot.json";
+
+    fn new(settings: &Settings, memory: Option<u64>, outcome: Outcome) -> Self {
+        let file = fs::canonicalize(&settings.file).unwrap_or_else(|_| settings.file.clone());
+        Self { file, timeout: settings.timeout, line_limit: settings.line_limit, memory, outcome }
+    }
+
+    /// The source of the snapshot in `output_dir`, if it matches the current
+    /// settings.
+    fn reusable(output_dir: &Path, settings: &Settings, memory: Option<u64>) -> Option<Self> {
+        let saved = fs::read_to_string(output_dir.join(Self::FILE_NAME)).ok()?;
+        let saved: Self = serde_json::from_str(&saved).ok()?;
+        let current = Self::new(settings, memory, saved.outcome.clone());
+        (saved == current).then_some(saved)
+    }
+}
+
+/// The files written by a batch run.
+#[derive(Serialize)]
+pub struct Batch {
+    output_dir: PathBuf,
+    /// Whether the log was loaded from the snapshot of a previous run.
+    reused: bool,
+    written: Vec<PathBuf>,
+}
+
+/// The output directory of a batch run.
+struct Output<'a> {
+    dir: &'a Path,
+    verbose: bool,
+    written: Vec<PathBuf>,
+}
+
+impl Output<'_> {
+    fn progress(&self, msg: fmt::Arguments) {
+        if self.verbose {
+            eprintln!("{msg}");
+        }
+    }
+    fn wrote(&mut self, path: PathBuf) {
+        self.progress(format_args!("Wrote {}", path.display()));
+        self.written.push(path);
+    }
+    fn json(&mut self, name: &str, data: &impl Serialize) -> CliResult<()> {
+        let path = self.dir.join(name);
+        fs::write(&path, serde_json::to_string_pretty(data)?)?;
+        self.wrote(path);
+        Ok(())
+    }
+}
+
+pub fn batch(settings: &Path, output_dir: &Path, memory: Option<u64>) -> CliResult<Report<Batch>> {
+    let settings = Settings::load(settings)?;
+    fs::create_dir_all(output_dir)?;
+    let mut output = Output { dir: output_dir, verbose: settings.verbose, written: Vec::new() };
+    let snapshot = output_dir.join("log").with_extension(SNAPSHOT_EXTENSION);
+    let source = if settings.reuses && snapshot.is_file() { SnapshotSource::reusable(output_dir, &settings, memory) } else { None };
+    let reused = source.is_some();
+    let limits = settings.limits(memory);
+    let mut loaded = if let Some(source) = source {
+        output.progress(format_args!("Reusing {}", snapshot.display()));
+        let mut loaded = Loaded::load(&snapshot, limits)?;
+        // Report why parsing of the original log stopped.
+        loaded.outcome = source.outcome;
+        loaded
+    } else {
+        output.progress(format_args!("Parsing {}", settings.file.display()));
+        Loaded::load(&settings.file, limits)?
+    };
+    output.progress(format_args!("Parsing {} after {:.2}s", loaded.outcome, loaded.elapsed.as_secs_f32()));
+
+    output.json("summary.json", &FileStats::new(&settings.file, &loaded))?;
+    if settings.save_all_data {
+        let quantifiers = Quantifiers::new(&loaded, CostKind::default(), QuantOrder::Cost, None);
+        output.json("quantifiers.json", &quantifiers)?;
+    }
+    if (settings.save_all_data || settings.reuses) && !reused {
+        let graph = loaded.take_graph();
+        let complete = loaded.outcome == Outcome::Completed;
+        loaded.parser.save_snapshot_file(Some(&graph), complete, &snapshot)?;
+        output.wrote(snapshot);
+        loaded.graph = Some(graph);
+        output.json(SnapshotSource::FILE_NAME, &SnapshotSource::new(&settings, memory, loaded.outcome.clone()))?;
+    }
+    let limit = match settings.number_inst {
+        0 => usize::MAX,
+        n => n,
+    };
+    let top = Top::new(&mut loaded, settings.sort_by, CostKind::default(), limit);
+    output.json("instantiations.json", &top)?;
+
+    let batch = Batch { output_dir: output_dir.to_path_buf(), reused, written: output.written };
+    Ok(Report::new(&settings.file, &loaded, batch))
+}
+
+impl fmt::Display for Batch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.reused {
+            writeln!(f, "Reused the snapshot in {}", self.output_dir.display())?;
+        }
+        for path in &self.written {
+            writeln!(f, "Wrote {}", path.display())?;
+        }
+        Ok(())
+    }
+}
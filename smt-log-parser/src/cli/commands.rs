@@ -0,0 +1,605 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeSet,
+    fmt, fs,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use serde::Serialize;
+use smt_log_parser::{
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    items::InstIdx,
+    parsers::z3::{
+        budget::{Budget, Violation},
+        cost::CostKind,
+        diff::{DiffStatus, LogDiff, QuantDiff, QuantSummary},
+        export::ExportedGraph,
+        z3parser::QuantStats,
+    },
+    Z3Parser,
+};
+
+use super::{
+    load::{Limits, Loaded, Outcome},
+    CliResult, Emit, ExportFormat, QuantOrder, TopOrder,
+};
+
+/// The results of a command on a single log.
+#[derive(Serialize)]
+pub struct Report<T> {
+    file: PathBuf,
+    outcome: Outcome,
+    pub results: T,
+}
+
+impl<T> Report<T> {
+    pub fn new(file: &Path, loaded: &Loaded, results: T) -> Self {
+        Self { file: file.to_path_buf(), outcome: loaded.outcome.clone(), results }
+    }
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+}
+
+impl<T: Serialize + fmt::Display> Emit for Report<T> {
+    fn failed(&self) -> bool {
+        self.outcome.is_error()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Report<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.outcome != Outcome::Completed {
+            writeln!(f, "Parsing {} {}, the results are partial", self.file.display(), self.outcome)?;
+        }
+        write!(f, "{}", self.results)
+    }
+}
+
+fn ctxt(parser: &Z3Parser) -> DisplayCtxt<'_> {
+    DisplayCtxt {
+        parser,
+        display_term_ids: false,
+        display_quantifier_name: false,
+        use_mathematical_symbols: true,
+    }
+}
+
+// Stats
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct Stats(Vec<FileStats>);
+
+#[derive(Serialize)]
+pub struct FileStats {
+    file: PathBuf,
+    outcome: Outcome,
+    seconds: f32,
+    quantifiers: usize,
+    matches: usize,
+    unused_matches: usize,
+    insts: usize,
+    checks: usize,
+    equality_mismatches: usize,
+    diagnostics: Vec<DiagnosticCount>,
+    quirks: Vec<QuirkCount>,
+}
+
+#[derive(Serialize)]
+pub struct DiagnosticCount {
+    kind: &'static str,
+    count: usize,
+    first_line: usize,
+}
+
+#[derive(Serialize)]
+pub struct QuirkCount {
+    quirk: &'static str,
+    count: usize,
+}
+
+pub fn stats(files: &[PathBuf], limits: Limits) -> CliResult<Stats> {
+    let mut stats = Vec::new();
+    for file in files {
+        let loaded = Loaded::load(file, limits)?;
+        stats.push(FileStats::new(file, &loaded));
+    }
+    Ok(Stats(stats))
+}
+
+impl FileStats {
+    pub fn new(file: &Path, loaded: &Loaded) -> Self {
+        let parser = &loaded.parser;
+        let diagnostics = parser.diagnostics().by_kind();
+        Self {
+            file: file.to_path_buf(),
+            outcome: loaded.outcome.clone(),
+            seconds: loaded.elapsed.as_secs_f32(),
+            quantifiers: parser.quantifiers().len(),
+            matches: parser.matches().len(),
+            unused_matches: parser.unused_matches().count(),
+            insts: parser.insts().len(),
+            checks: parser.checks().len(),
+            equality_mismatches: parser.equality_mismatches().len(),
+            diagnostics: diagnostics
+                .into_iter()
+                .map(|d| DiagnosticCount { kind: d.kind, count: d.count, first_line: d.first_line })
+                .collect(),
+            quirks: parser.quirks().fired().map(|(quirk, count)| QuirkCount { quirk: quirk.name(), count }).collect(),
+        }
+    }
+}
+
+impl Emit for Stats {
+    fn failed(&self) -> bool {
+        self.0.iter().any(|stats| stats.outcome.is_error())
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stats in &self.0 {
+            writeln!(f, "{}: {} after {:.2}s", stats.file.display(), stats.outcome, stats.seconds)?;
+            writeln!(f, "  {} quantifiers, {} checks", stats.quantifiers, stats.checks)?;
+            writeln!(
+                f,
+                "  {} matches ({} never instantiated), {} instantiations",
+                stats.matches, stats.unused_matches, stats.insts
+            )?;
+            if stats.equality_mismatches > 0 {
+                writeln!(f, "  {} equality lookups depend on ignoring popped stack frames", stats.equality_mismatches)?;
+            }
+            for d in &stats.diagnostics {
+                writeln!(f, "  {} `{}` errors starting at line {}", d.count, d.kind, d.first_line)?;
+            }
+            for q in &stats.quirks {
+                writeln!(f, "  worked around {} {} times", q.quirk, q.count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Quantifiers
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct Quantifiers(Vec<QuantRow>);
+
+#[derive(Serialize)]
+pub struct QuantRow {
+    name: String,
+    matches: usize,
+    insts: usize,
+    unused_matches: usize,
+    inst_ratio: Option<f32>,
+    patterns: Vec<PatternRow>,
+    max_generation: Option<u32>,
+    mean_generation: Option<f32>,
+    yields: usize,
+    cost: f32,
+    self_cost: f32,
+}
+
+#[derive(Serialize)]
+pub struct PatternRow {
+    pattern: String,
+    matches: usize,
+    insts: usize,
+}
+
+pub fn quantifiers(
+    file: &Path,
+    limits: Limits,
+    cost_model: CostKind,
+    sort_by: QuantOrder,
+    limit: Option<usize>,
+) -> CliResult<Report<Quantifiers>> {
+    let loaded = Loaded::load(file, limits)?;
+    let quantifiers = Quantifiers::new(&loaded, cost_model, sort_by, limit);
+    Ok(Report::new(file, &loaded, quantifiers))
+}
+
+impl Quantifiers {
+    pub fn new(loaded: &Loaded, cost_model: CostKind, sort_by: QuantOrder, limit: Option<usize>) -> Self {
+        let parser = &loaded.parser;
+        let ctxt = ctxt(parser);
+        let stats = parser.quant_stats(&parser.inst_costs(&cost_model));
+        let mut rows: Vec<_> = stats
+            .into_iter_enumerated()
+            .filter(|(_, stats)| stats.matches > 0)
+            .map(|(quant, stats)| QuantRow::new(parser.quant_name(quant), stats, &ctxt))
+            .collect();
+        // Stable, so ties stay in quantifier order.
+        match sort_by {
+            QuantOrder::Name => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+            QuantOrder::Matches => rows.sort_by_key(|row| Reverse(row.matches)),
+            QuantOrder::Insts => rows.sort_by_key(|row| Reverse(row.insts)),
+            QuantOrder::Unused => rows.sort_by_key(|row| Reverse(row.unused_matches)),
+            QuantOrder::Cost => rows.sort_by(|a, b| b.cost.total_cmp(&a.cost)),
+            QuantOrder::SelfCost => rows.sort_by(|a, b| b.self_cost.total_cmp(&a.self_cost)),
+        }
+        if let Some(limit) = limit {
+            rows.truncate(limit);
+        }
+        Self(rows)
+    }
+}
+
+impl QuantRow {
+    fn new(name: String, stats: QuantStats, ctxt: &DisplayCtxt) -> Self {
+        let patterns = stats
+            .patterns
+            .iter()
+            .map(|p| PatternRow { pattern: p.pattern.with(ctxt).to_string(), matches: p.matches, insts: p.insts })
+            .collect();
+        Self {
+            name,
+            matches: stats.matches,
+            insts: stats.insts,
+            unused_matches: stats.unused_matches,
+            inst_ratio: stats.inst_ratio(),
+            patterns,
+            max_generation: stats.max_generation,
+            mean_generation: stats.mean_generation,
+            yields: stats.yields,
+            cost: stats.cost,
+            self_cost: stats.self_cost,
+        }
+    }
+}
+
+impl fmt::Display for Quantifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>9} {:>9} {:>9} {:>6} {:>7} {:>8} {:>9} {:>10} {:>10}  quantifier",
+            "matches", "insts", "unused", "ratio", "max gen", "mean gen", "yields", "cost", "self cost"
+        )?;
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        for row in &self.0 {
+            writeln!(
+                f,
+                "{:>9} {:>9} {:>9} {:>6} {:>7} {:>8} {:>9} {:>10.1} {:>10.1}  {}",
+                row.matches,
+                row.insts,
+                row.unused_matches,
+                or_dash(row.inst_ratio.map(|r| format!("{:.0}%", r * 100.0))),
+                or_dash(row.max_generation.map(|g| g.to_string())),
+                or_dash(row.mean_generation.map(|g| format!("{g:.2}"))),
+                row.yields,
+                row.cost,
+                row.self_cost,
+                row.name,
+            )?;
+            for p in &row.patterns {
+                writeln!(f, "{:>9} {:>9} {:66}  pattern {}", p.matches, p.insts, "", p.pattern)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Top
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct Top(Vec<InstRow>);
+
+#[derive(Serialize)]
+pub struct InstRow {
+    inst: usize,
+    quantifier: Option<String>,
+    cost: f32,
+    children: usize,
+    depth: Option<usize>,
+}
+
+pub fn top(file: &Path, limits: Limits, by: TopOrder, cost_model: CostKind, limit: usize) -> CliResult<Report<Top>> {
+    let mut loaded = Loaded::load(file, limits)?;
+    let top = Top::new(&mut loaded, by, cost_model, limit);
+    Ok(Report::new(file, &loaded, top))
+}
+
+impl Top {
+    pub fn new(loaded: &mut Loaded, by: TopOrder, cost_model: CostKind, limit: usize) -> Self {
+        let mut graph = loaded.take_graph();
+        graph.set_cost_model(&loaded.parser, cost_model);
+        let mut nodes: Vec<_> = graph.orig_graph.node_weights().collect();
+        match by {
+            TopOrder::Cost => nodes.sort_by_key(|node| node.cost_rank()),
+            TopOrder::Branching => nodes.sort_by_key(|node| node.branching_rank()),
+            TopOrder::LineNo => nodes.sort_by_key(|node| node.inst_idx),
+        }
+        let parser = &loaded.parser;
+        let rows = nodes
+            .into_iter()
+            .take(limit)
+            .map(|node| InstRow {
+                inst: node.inst_idx.into(),
+                quantifier: node.mkind.quant_idx().map(|quant| parser.quant_name(quant)),
+                cost: node.cost(),
+                children: node.child_count(),
+                depth: node.min_depth,
+            })
+            .collect();
+        Top(rows)
+    }
+}
+
+impl fmt::Display for Top {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>9} {:>10} {:>9} {:>6}  quantifier", "inst", "cost", "children", "depth")?;
+        for row in &self.0 {
+            let depth = row.depth.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string());
+            let quantifier = row.quantifier.as_deref().unwrap_or("<theory solving>");
+            writeln!(f, "{:>9} {:>10.1} {:>9} {:>6}  {quantifier}", row.inst, row.cost, row.children, depth)?;
+        }
+        Ok(())
+    }
+}
+
+// Loops
+
+#[derive(Serialize)]
+pub struct Loops {
+    /// The number of potential matching loops found, of which at most the
+    /// requested number are listed.
+    found: usize,
+    loops: Vec<MatchingLoop>,
+}
+
+#[derive(Serialize)]
+pub struct MatchingLoop {
+    insts: Vec<usize>,
+    quantifiers: BTreeSet<String>,
+    generalized_terms: Vec<String>,
+}
+
+pub fn loops(file: &Path, limits: Limits, limit: usize) -> CliResult<Report<Loops>> {
+    let mut loaded = Loaded::load(file, limits)?;
+    let mut graph = loaded.take_graph();
+    let found = graph.search_matching_loops();
+    let mut loops = Vec::new();
+    for n in 0..found.min(limit) {
+        let generalized_terms = graph.show_nth_matching_loop(n, &mut loaded.parser);
+        let nodes = graph.orig_graph.node_weights().filter(|node| node.visible());
+        let (insts, quants): (Vec<InstIdx>, Vec<_>) = nodes.map(|node| (node.inst_idx, node.mkind.quant_idx())).unzip();
+        let quantifiers = quants.into_iter().flatten().map(|quant| loaded.parser.quant_name(quant)).collect();
+        loops.push(MatchingLoop { insts: insts.into_iter().map(usize::from).collect(), quantifiers, generalized_terms });
+    }
+    Ok(Report::new(file, &loaded, Loops { found, loops }))
+}
+
+impl fmt::Display for Loops {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Found {} potential matching loops", self.found)?;
+        for (n, ml) in self.loops.iter().enumerate() {
+            let quantifiers: Vec<_> = ml.quantifiers.iter().map(String::as_str).collect();
+            writeln!(f, "{}. {} instantiations of {}", n + 1, ml.insts.len(), quantifiers.join(", "))?;
+            for term in &ml.generalized_terms {
+                writeln!(f, "   {term}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Check
+
+#[derive(Serialize)]
+pub struct BudgetCheck {
+    /// The number of quantifiers which have limits.
+    checked: usize,
+    violations: Vec<Violation>,
+}
+
+impl BudgetCheck {
+    pub fn exit_code(&self) -> ExitCode {
+        if self.violations.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+}
+
+pub fn check(file: &Path, limits: Limits, budget: &Path, cost_model: CostKind) -> CliResult<Report<BudgetCheck>> {
+    let budget: Budget = serde_json::from_str(&fs::read_to_string(budget)?)?;
+    let mut loaded = Loaded::load(file, limits)?;
+    let mut graph = loaded.take_graph();
+    graph.set_cost_model(&loaded.parser, cost_model);
+    let parser = &loaded.parser;
+    let checked = parser.quantifiers().keys().filter(|&quant| budget.limits_for(parser, quant).is_some()).count();
+    let violations = budget.check(parser, &mut graph);
+    Ok(Report::new(file, &loaded, BudgetCheck { checked, violations }))
+}
+
+impl fmt::Display for BudgetCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.violations.is_empty() {
+            return writeln!(f, "All {} quantifiers with a budget are within it", self.checked);
+        }
+        writeln!(f, "{} budget violations:", self.violations.len())?;
+        for violation in &self.violations {
+            writeln!(f, "  {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+// Diff
+
+#[derive(Serialize)]
+pub struct Diff {
+    old: PathBuf,
+    old_outcome: Outcome,
+    new: PathBuf,
+    new_outcome: Outcome,
+    quantifiers: Vec<DiffRow>,
+}
+
+#[derive(Serialize)]
+pub struct DiffRow {
+    status: DiffStatus,
+    #[serde(flatten)]
+    diff: QuantDiff,
+}
+
+pub fn diff(old: &Path, new: &Path, limits: Limits, cost_model: CostKind, all: bool) -> CliResult<Diff> {
+    let mut old_loaded = Loaded::load(old, limits)?;
+    let mut new_loaded = Loaded::load(new, limits)?;
+    let (mut old_graph, mut new_graph) = (old_loaded.take_graph(), new_loaded.take_graph());
+    old_graph.set_cost_model(&old_loaded.parser, cost_model);
+    new_graph.set_cost_model(&new_loaded.parser, cost_model);
+    let diff = LogDiff::new(&old_loaded.parser, &mut old_graph, &new_loaded.parser, &mut new_graph);
+    let quantifiers = diff
+        .quantifiers
+        .into_iter()
+        .map(|diff| DiffRow { status: diff.status(), diff })
+        .filter(|row| all || row.status != DiffStatus::Unchanged)
+        .collect();
+    Ok(Diff {
+        old: old.to_path_buf(),
+        old_outcome: old_loaded.outcome,
+        new: new.to_path_buf(),
+        new_outcome: new_loaded.outcome,
+        quantifiers,
+    })
+}
+
+impl Emit for Diff {
+    fn failed(&self) -> bool {
+        self.old_outcome.is_error() || self.new_outcome.is_error()
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (file, outcome) in [(&self.old, &self.old_outcome), (&self.new, &self.new_outcome)] {
+            if *outcome != Outcome::Completed {
+                writeln!(f, "Parsing {} {}, the results are partial", file.display(), outcome)?;
+            }
+        }
+        writeln!(f, "{:<9} {:>20} {:>26} {:>16} {:>16}  quantifier", "", "insts", "cost", "loops", "depth")?;
+        for row in &self.quantifiers {
+            let (old, new, delta) = (row.diff.old.as_ref(), row.diff.new.as_ref(), &row.diff.delta);
+            let side = |summary: Option<&QuantSummary>, f: fn(&QuantSummary) -> String| summary.map_or("-".to_string(), f);
+            let insts = format!("{} → {} ({:+})", side(old, |s| s.insts.to_string()), side(new, |s| s.insts.to_string()), delta.insts);
+            let cost =
+                format!("{} → {} ({:+.1})", side(old, |s| format!("{:.1}", s.cost)), side(new, |s| format!("{:.1}", s.cost)), delta.cost);
+            let loops = format!(
+                "{} → {} ({:+})",
+                side(old, |s| s.matching_loops.to_string()),
+                side(new, |s| s.matching_loops.to_string()),
+                delta.matching_loops
+            );
+            let depth = |s: &QuantSummary| s.max_depth.map_or("-".to_string(), |d| d.to_string());
+            let depth = format!("{} → {} ({:+})", side(old, depth), side(new, depth), delta.max_depth);
+            let status = match row.status {
+                DiffStatus::Added => "added",
+                DiffStatus::Removed => "removed",
+                DiffStatus::Changed => "changed",
+                DiffStatus::Unchanged => "",
+            };
+            let name = match (old, new) {
+                (Some(old), Some(new)) if old.name != new.name => format!("{} (was {})", new.name, old.name),
+                _ => row.diff.name().to_string(),
+            };
+            writeln!(f, "{status:<9} {insts:>20} {cost:>26} {loops:>16} {depth:>16}  {name}")?;
+        }
+        Ok(())
+    }
+}
+
+// Export
+
+#[derive(Serialize)]
+pub struct Exported {
+    output: PathBuf,
+    /// Why parsing stopped, anything but completed means only part of the
+    /// log was exported.
+    outcome: Outcome,
+    /// The number of instantiations and dependencies written, `None` for
+    /// snapshots.
+    nodes: Option<usize>,
+    edges: Option<usize>,
+}
+
+/// Writes an instantiation graph in one of the graph formats.
+type WriteGraph = fn(&ExportedGraph, &mut BufWriter<fs::File>) -> CliResult<()>;
+
+pub fn export(
+    file: &Path,
+    limits: Limits,
+    output: &Path,
+    format: ExportFormat,
+    cost_model: Option<CostKind>,
+    matching_loops: bool,
+) -> CliResult<Report<Exported>> {
+    let write: WriteGraph = match format {
+        ExportFormat::Snapshot => {
+            if cost_model.is_some() || matching_loops {
+                return Err("`--cost-model` and `--matching-loops` only apply to the graph formats".into());
+            }
+            return export_snapshot(file, limits, output);
+        }
+        ExportFormat::Graphml => |graph, out| Ok(graph.write_graphml(out)?),
+        ExportFormat::Gexf => |graph, out| Ok(graph.write_gexf(out)?),
+        ExportFormat::Json => |graph, out| Ok(serde_json::to_writer_pretty(out, graph)?),
+    };
+    export_graph(file, limits, output, write, cost_model.unwrap_or_default(), matching_loops)
+}
+
+fn export_snapshot(file: &Path, limits: Limits, output: &Path) -> CliResult<Report<Exported>> {
+    let mut loaded = Loaded::load(file, limits)?;
+    if loaded.outcome != Outcome::Completed {
+        let err = format!("parsing {} {}, snapshots are only exported of completely parsed logs", file.display(), loaded.outcome);
+        return Err(err.into());
+    }
+    let graph = loaded.take_graph();
+    loaded.parser.save_snapshot_file(Some(&graph), true, output)?;
+    let exported = Exported { output: output.to_path_buf(), outcome: loaded.outcome.clone(), nodes: None, edges: None };
+    Ok(Report::new(file, &loaded, exported))
+}
+
+fn export_graph(
+    file: &Path,
+    limits: Limits,
+    output: &Path,
+    write: WriteGraph,
+    cost_model: CostKind,
+    matching_loops: bool,
+) -> CliResult<Report<Exported>> {
+    let mut loaded = Loaded::load(file, limits)?;
+    let mut graph = loaded.take_graph();
+    graph.set_cost_model(&loaded.parser, cost_model);
+    let exported = if matching_loops {
+        graph.search_matching_loops();
+        graph.show_matching_loop_subgraph();
+        graph.retain_visible_nodes_and_reconnect();
+        ExportedGraph::visible(&loaded.parser, &graph)
+    } else {
+        ExportedGraph::orig(&loaded.parser, &graph)
+    };
+    let mut out = BufWriter::new(fs::File::create(output)?);
+    write(&exported, &mut out)?;
+    out.flush()?;
+    let (nodes, edges) = (Some(exported.nodes.len()), Some(exported.edges.len()));
+    let exported = Exported { output: output.to_path_buf(), outcome: loaded.outcome.clone(), nodes, edges };
+    Ok(Report::new(file, &loaded, exported))
+}
+
+impl fmt::Display for Exported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.nodes.zip(self.edges) {
+            Some((nodes, edges)) => {
+                writeln!(f, "Wrote {nodes} instantiations and {edges} dependencies to {}", self.output.display())
+            }
+            None => writeln!(f, "Wrote {}", self.output.display()),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fmt,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use smt_log_parser::{
+    parsers::{z3::inst_graph::InstGraph, LogParser, ParseState, StreamParser},
+    Cvc5Parser, Z3Parser,
+};
+
+use super::CliResult;
+
+/// Files with this extension are read as cvc5 instantiation traces.
+const CVC5_EXTENSION: &str = "cvc5";
+/// Files with this extension are read as
+/// [snapshots](smt_log_parser::parsers::z3::snapshot).
+pub const SNAPSHOT_EXTENSION: &str = "snap";
+
+/// When to stop parsing a log early, in which case the analyses run on what
+/// was parsed up to that point.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub timeout: Duration,
+    /// The maximum physical memory used by the whole process, in bytes.
+    pub memory: Option<u64>,
+    /// The maximum number of lines of the log to parse.
+    pub lines: Option<usize>,
+}
+
+impl Limits {
+    /// How many lines to parse between checking the time and memory used.
+    const LINES_PER_CHECK: usize = 1024;
+}
+
+/// Why parsing stopped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    Completed,
+    Timeout,
+    MemoryLimit,
+    LineLimit,
+    /// Loaded from a snapshot of a log which was not parsed completely.
+    PartialSnapshot,
+    Error(String),
+}
+
+impl Outcome {
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Error(_))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Completed => write!(f, "completed"),
+            Self::Timeout => write!(f, "stopped at the timeout"),
+            Self::MemoryLimit => write!(f, "stopped at the memory limit"),
+            Self::LineLimit => write!(f, "stopped at the line limit"),
+            Self::PartialSnapshot => write!(f, "stopped before the end of the log when it was snapshotted"),
+            Self::Error(err) => write!(f, "failed with {err}"),
+        }
+    }
+}
+
+/// A parsed log, possibly only partially.
+pub struct Loaded {
+    pub parser: Z3Parser,
+    /// Only present when loaded from a snapshot which contained one.
+    pub graph: Option<InstGraph>,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+impl Loaded {
+    pub fn load(path: &Path, limits: Limits) -> CliResult<Self> {
+        let start = Instant::now();
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let (parser, graph, outcome) = match extension {
+            Some(SNAPSHOT_EXTENSION) => {
+                let snapshot = Z3Parser::load_snapshot_file(path)?;
+                let outcome = if snapshot.complete { Outcome::Completed } else { Outcome::PartialSnapshot };
+                (snapshot.parser, snapshot.graph, outcome)
+            }
+            Some(CVC5_EXTENSION) => {
+                let (_metadata, parser) = Cvc5Parser::from_file(path)?;
+                let (parser, outcome) = Self::process(parser, limits);
+                (parser.into_model(), None, outcome)
+            }
+            _ => {
+                let (_metadata, parser) = Z3Parser::from_file(path)?;
+                let (parser, outcome) = Self::process(parser, limits);
+                (parser, None, outcome)
+            }
+        };
+        Ok(Self { parser, graph, outcome, elapsed: start.elapsed() })
+    }
+
+    /// The instantiation graph, built now unless it came with the snapshot.
+    pub fn take_graph(&mut self) -> InstGraph {
+        self.graph.take().unwrap_or_else(|| InstGraph::from(&self.parser))
+    }
+
+    fn process<P: LogParser>(mut parser: StreamParser<'_, P>, limits: Limits) -> (P, Outcome) {
+        let start = Instant::now();
+        let mut stopped = Outcome::Timeout;
+        let state = parser.process_until(|_, state| {
+            if limits.lines.is_some_and(|lines| state.lines_read >= lines) {
+                stopped = Outcome::LineLimit;
+                return false;
+            }
+            if state.lines_read % Limits::LINES_PER_CHECK != 0 {
+                return true;
+            }
+            if start.elapsed() >= limits.timeout {
+                stopped = Outcome::Timeout;
+                return false;
+            }
+            let used = memory_stats::memory_stats().map(|stats| stats.physical_mem as u64);
+            if limits.memory.zip(used).is_some_and(|(limit, used)| used > limit) {
+                stopped = Outcome::MemoryLimit;
+                return false;
+            }
+            true
+        });
+        let outcome = match state {
+            ParseState::Completed { .. } => Outcome::Completed,
+            ParseState::Paused(_) | ParseState::Waiting(_) => stopped,
+            ParseState::Error(err) => Outcome::Error(format!("{err:?}")),
+        };
+        (parser.take_parser(), outcome)
+    }
+}
@@ -0,0 +1,250 @@
+//! The `smt-log-parser` command-line tool, running the analyses of the GUI
+//! headlessly (e.g. in CI).
+
+mod batch;
+mod commands;
+mod load;
+
+use std::{path::PathBuf, process::ExitCode, time::Duration};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use smt_log_parser::parsers::z3::cost::CostKind;
+
+use self::load::{Limits, Outcome};
+
+pub type CliResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(version, about = "Analyse the quantifier instantiations of Z3 (or cvc5) logs")]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Print the results as JSON rather than in human-readable form.
+    #[arg(long, global = true)]
+    json: bool,
+    /// Stop parsing each log after this many seconds and analyse what was
+    /// parsed up to then [default: 15, or none for `check`]. Batch runs use
+    /// the timeout of their settings instead.
+    #[arg(long, global = true, value_parser = parse_timeout, allow_negative_numbers = true)]
+    timeout: Option<f32>,
+    /// Stop parsing each log once the process uses more than this many MiB
+    /// of memory and analyse what was parsed up to then.
+    #[arg(long, global = true)]
+    memory_limit: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Summarise each log: what was parsed and any problems encountered.
+    Stats {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Per-quantifier statistics, like Z3's `qi` profiler output.
+    Quantifiers {
+        file: PathBuf,
+        #[command(flatten)]
+        cost: CostArgs,
+        #[arg(long, value_enum, default_value_t = QuantOrder::Cost)]
+        sort_by: QuantOrder,
+        /// Only show this many quantifiers.
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+    /// The instantiations with the highest cost or the most dependents.
+    Top {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = TopOrder::Cost)]
+        by: TopOrder,
+        #[command(flatten)]
+        cost: CostArgs,
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Search for matching loops, longest first.
+    Loops {
+        file: PathBuf,
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Run the analysis configured by a `settings.json` or `settings.txt`
+    /// file, writing the results to a directory.
+    Batch {
+        settings: PathBuf,
+        #[arg(short, long)]
+        output_dir: PathBuf,
+    },
+    /// Check the instantiations against the limits in a JSON budget file,
+    /// exiting with status 1 if any are exceeded. The whole log must be
+    /// parsed, otherwise the check fails with status 2.
+    Check {
+        file: PathBuf,
+        #[arg(short, long)]
+        budget: PathBuf,
+        #[command(flatten)]
+        cost: CostArgs,
+    },
+    /// Compare the quantifier instantiations of two logs, e.g. before and
+    /// after a change which slowed down a proof.
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[command(flatten)]
+        cost: CostArgs,
+        /// Also list the quantifiers which did not change.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Write the parsed log, or its instantiation graph, to a file.
+    Export {
+        file: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Snapshot)]
+        format: ExportFormat,
+        /// How to attribute costs to instantiations, defaults to
+        /// `split-share` (graph formats only).
+        #[arg(long, value_parser = CostArgs::parse)]
+        cost_model: Option<CostKind>,
+        /// Only export the instantiations which are part of matching loops
+        /// (graph formats only).
+        #[arg(long)]
+        matching_loops: bool,
+    },
+}
+
+#[derive(Args)]
+struct CostArgs {
+    /// How to attribute costs to instantiations.
+    #[arg(long, default_value_t = CostKind::default(), value_parser = CostArgs::parse)]
+    cost_model: CostKind,
+}
+
+impl CostArgs {
+    fn parse(name: &str) -> Result<CostKind, String> {
+        CostKind::from_name(name).ok_or_else(|| {
+            let names: Vec<_> = CostKind::ALL.iter().map(|kind| kind.to_string()).collect();
+            format!("expected one of {}", names.join(", "))
+        })
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum QuantOrder {
+    Name,
+    Matches,
+    Insts,
+    Unused,
+    Cost,
+    SelfCost,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TopOrder {
+    Cost,
+    Branching,
+    /// The order in which they appear in the log.
+    LineNo,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// A binary snapshot, which all commands load much faster than the
+    /// original log when given a file with the `.snap` extension.
+    Snapshot,
+    /// The instantiation graph as GraphML, e.g. for yEd.
+    Graphml,
+    /// The instantiation graph as GEXF, e.g. for Gephi.
+    Gexf,
+    /// The instantiation graph as JSON, see `ExportedGraph` for the schema.
+    Json,
+}
+
+impl Cli {
+    const DEFAULT_TIMEOUT: f32 = 15.0;
+
+    pub fn run(self) -> ExitCode {
+        let limits = Limits {
+            timeout: Duration::from_secs_f32(self.timeout.unwrap_or(Self::DEFAULT_TIMEOUT)),
+            memory: self.memory_limit.map(|mib| mib * 1024 * 1024),
+            lines: None,
+        };
+        let json = self.json;
+        let result = match self.command {
+            Command::Stats { files } => commands::stats(&files, limits).map(|r| emit(&r, json)),
+            Command::Quantifiers { file, cost, sort_by, limit } => {
+                commands::quantifiers(&file, limits, cost.cost_model, sort_by, limit).map(|r| emit(&r, json))
+            }
+            Command::Top { file, by, cost, limit } => {
+                commands::top(&file, limits, by, cost.cost_model, limit).map(|r| emit(&r, json))
+            }
+            Command::Loops { file, limit } => commands::loops(&file, limits, limit).map(|r| emit(&r, json)),
+            Command::Batch { settings, output_dir } => {
+                batch::batch(&settings, &output_dir, limits.memory).map(|r| emit(&r, json))
+            }
+            Command::Check { file, budget, cost } => {
+                // A partial log could hide violations, so only stop early if
+                // asked to.
+                let limits = Limits { timeout: self.timeout.map_or(Duration::MAX, Duration::from_secs_f32), ..limits };
+                commands::check(&file, limits, &budget, cost.cost_model).map(|r| {
+                    emit(&r, json);
+                    if *r.outcome() != Outcome::Completed {
+                        eprintln!("error: parsing {} {}, the budget was not checked", file.display(), r.outcome());
+                        ExitCode::from(2)
+                    } else {
+                        r.results.exit_code()
+                    }
+                })
+            }
+            Command::Diff { old, new, cost, all } => {
+                commands::diff(&old, &new, limits, cost.cost_model, all).map(|r| emit(&r, json))
+            }
+            Command::Export { file, output, format, cost_model, matching_loops } => {
+                commands::export(&file, limits, &output, format, cost_model, matching_loops).map(|r| {
+                    let code = emit(&r, json);
+                    if !matches!(r.outcome(), Outcome::Completed | Outcome::Error(_)) {
+                        eprintln!("warning: parsing {} {}, only part of it was exported", file.display(), r.outcome());
+                    }
+                    code
+                })
+            }
+        };
+        result.unwrap_or_else(|err| {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        })
+    }
+}
+
+/// Only accepts timeouts which fit in a `Duration`, so that converting them
+/// cannot panic.
+fn parse_timeout(secs: &str) -> Result<f32, String> {
+    let secs: f32 = secs.parse().map_err(|err| format!("{err}"))?;
+    match Duration::try_from_secs_f32(secs) {
+        Ok(_) => Ok(secs),
+        Err(_) => Err("expected a non-negative number of seconds which is not too large".to_string()),
+    }
+}
+
+/// The results of a command, which may be partial.
+trait Emit: Serialize + std::fmt::Display {
+    /// Did parsing a log fail with an error?
+    fn failed(&self) -> bool;
+}
+
+/// Print a report in the selected format. Reports of logs which failed to
+/// parse are still printed, but exit with the same status as other errors.
+fn emit<R: Emit>(report: &R, json: bool) -> ExitCode {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report).expect("reports are valid JSON"));
+    } else {
+        print!("{report}");
+    }
+    if report.failed() {
+        ExitCode::from(2)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
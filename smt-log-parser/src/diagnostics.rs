@@ -0,0 +1,200 @@
+use std::fmt;
+
+use fxhash::FxHashMap;
+
+use crate::{Error, FResult, FatalError};
+
+/// What to do when a line of the log fails to parse with a recoverable
+/// [`Error`]. The number of errors of each kind is always tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiagnosticPolicy {
+    /// Only count the error.
+    Ignore,
+    /// Record the error with its position and the raw line, then continue.
+    #[default]
+    Collect,
+    /// Record the error and stop parsing with [`FatalError::TooManyErrors`]
+    /// once more than `n` errors have been seen. Use `AbortAfter(0)` to fail
+    /// on the first error.
+    AbortAfter(usize),
+}
+
+/// Options which can be passed to a parser with
+/// [`set_options`](crate::LogParser::set_options) before parsing starts.
+#[derive(Debug, Clone, Default)]
+pub struct ParserOptions {
+    pub diagnostics: DiagnosticPolicy,
+}
+
+/// A recoverable error which was encountered while parsing a line.
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// The line number (starting from 1) of the line.
+    pub line_no: usize,
+    /// The offset in bytes of the start of the line.
+    pub byte_offset: usize,
+    /// The raw line which failed to parse.
+    pub line: String,
+    pub error: Error,
+}
+
+/// How often errors of a single kind (see [`Error::kind`]) were seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KindSummary {
+    pub kind: &'static str,
+    pub count: usize,
+    /// The line number of the first error of this kind.
+    pub first_line: usize,
+}
+
+impl fmt::Display for KindSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.count == 1 { "" } else { "s" };
+        write!(f, "{} `{}` error{plural} starting at line {}", self.count, self.kind, self.first_line)
+    }
+}
+
+/// All recoverable errors encountered so far by a parser.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    policy: DiagnosticPolicy,
+    total: usize,
+    by_kind: FxHashMap<&'static str, KindSummary>,
+    collected: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn policy(&self) -> DiagnosticPolicy {
+        self.policy
+    }
+    pub(crate) fn set_policy(&mut self, policy: DiagnosticPolicy) {
+        self.policy = policy;
+    }
+
+    /// Total number of recoverable errors seen.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+    /// The recorded errors in the order they were encountered. Empty if the
+    /// policy is [`DiagnosticPolicy::Ignore`].
+    pub fn collected(&self) -> &[Diagnostic] {
+        &self.collected
+    }
+    /// The recorded errors of a single kind.
+    pub fn collected_of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a Diagnostic> + 'a {
+        self.collected.iter().filter(move |d| d.error.kind() == kind)
+    }
+    /// The number of errors per kind, most frequent first.
+    pub fn by_kind(&self) -> Vec<KindSummary> {
+        let mut by_kind: Vec<_> = self.by_kind.values().copied().collect();
+        by_kind.sort_by(|a, b| b.count.cmp(&a.count).then(a.first_line.cmp(&b.first_line)));
+        by_kind
+    }
+
+    /// Record a recoverable error, returns an error if parsing should be
+    /// aborted.
+    pub(crate) fn report(&mut self, line_no: usize, byte_offset: usize, line: &str, error: Error) -> FResult<()> {
+        self.total += 1;
+        self.by_kind.try_reserve(1).map_err(FatalError::Allocation)?;
+        self.by_kind
+            .entry(error.kind())
+            .or_insert(KindSummary { kind: error.kind(), count: 0, first_line: line_no })
+            .count += 1;
+        if self.policy == DiagnosticPolicy::Ignore {
+            return Ok(());
+        }
+        self.collected.try_reserve(1).map_err(FatalError::Allocation)?;
+        self.collected.push(Diagnostic { line_no, byte_offset, line: line.to_string(), error });
+        match self.policy {
+            DiagnosticPolicy::AbortAfter(n) if self.total > n => Err(FatalError::TooManyErrors(self.total)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no errors");
+        }
+        for (i, kind) in self.by_kind().iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{kind}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report_all(policy: DiagnosticPolicy, errors: Vec<Error>) -> (Diagnostics, FResult<()>) {
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.set_policy(policy);
+        let mut result = Ok(());
+        for (idx, error) in errors.into_iter().enumerate() {
+            result = diagnostics.report(idx + 1, idx * 10, "line", error);
+            if result.is_err() {
+                break;
+            }
+        }
+        (diagnostics, result)
+    }
+
+    fn errors() -> Vec<Error> {
+        vec![
+            Error::UnexpectedEnd,
+            Error::UnknownLine("a".to_string()),
+            Error::UnknownLine("b".to_string()),
+            Error::UnexpectedEnd,
+            Error::UnknownLine("c".to_string()),
+        ]
+    }
+
+    #[test]
+    fn ignore_only_counts() {
+        let (diagnostics, result) = report_all(DiagnosticPolicy::Ignore, errors());
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.total(), 5);
+        assert!(diagnostics.collected().is_empty());
+        assert_eq!(diagnostics.by_kind().len(), 2);
+    }
+
+    #[test]
+    fn collect_records_every_error() {
+        let (diagnostics, result) = report_all(DiagnosticPolicy::Collect, errors());
+        assert!(result.is_ok());
+        let lines: Vec<_> = diagnostics.collected().iter().map(|d| (d.line_no, d.byte_offset)).collect();
+        assert_eq!(lines, [(1, 0), (2, 10), (3, 20), (4, 30), (5, 40)]);
+        let unknown: Vec<_> = diagnostics.collected_of_kind("UnknownLine").map(|d| d.line_no).collect();
+        assert_eq!(unknown, [2, 3, 5]);
+    }
+
+    #[test]
+    fn abort_after_n() {
+        let (diagnostics, result) = report_all(DiagnosticPolicy::AbortAfter(2), errors());
+        assert!(matches!(result, Err(FatalError::TooManyErrors(3))));
+        assert_eq!(diagnostics.total(), 3);
+        assert_eq!(diagnostics.collected().len(), 3);
+
+        let (_, result) = report_all(DiagnosticPolicy::AbortAfter(5), errors());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn grouped_by_kind() {
+        let (diagnostics, _) = report_all(DiagnosticPolicy::Collect, errors());
+        let expected = [
+            KindSummary { kind: "UnknownLine", count: 3, first_line: 2 },
+            KindSummary { kind: "UnexpectedEnd", count: 2, first_line: 1 },
+        ];
+        assert_eq!(diagnostics.by_kind(), expected);
+        assert_eq!(diagnostics.to_string(), "3 `UnknownLine` errors starting at line 2\n2 `UnexpectedEnd` errors starting at line 1");
+    }
+}
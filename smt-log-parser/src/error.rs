@@ -27,6 +27,10 @@ pub enum Error {
     VarNamesListInconsistent, // attach var names
     VarNamesNoBar,
     UnknownQuantifierIdx(TermIdx),
+    /// A `|quoted|` quantifier name without the closing bar
+    QuantNameNoBar(String),
+    /// No variable count between the quantifier name and its children
+    QuantNoNumVars(String),
 
     // Inst discovered
     /// theory-solving non-rewrite axiom should blame valid enodes
@@ -56,6 +60,21 @@ pub enum Error {
     StackFrameNotPushed,
     InvalidFrameInteger(ParseIntError),
 
+    // Check
+    UnknownCheckResult(String),
+
+    // Search
+    InvalidLiteral(String),
+    InvalidResolveLevel(ParseIntError),
+    ResolveWithoutConflict,
+
+    // cvc5
+    InvalidSExpr(String),
+    /// A list of terms outside of an `(instantiations ...)` block
+    UnexpectedInstantiation,
+    /// The number of terms does not match the number of bound variables
+    InstArityMismatch(usize, usize),
+
     Allocation(TryReserveError),
 }
 
@@ -72,15 +91,103 @@ impl From<TryReserveError> for Error {
 }
 
 impl Error {
-    pub fn as_fatal(self) -> Option<FatalError> {
+    pub fn as_fatal(&self) -> Option<FatalError> {
         match self {
-            Self::Allocation(alloc) => Some(FatalError::Allocation(alloc)),
+            Self::Allocation(alloc) => Some(FatalError::Allocation(alloc.clone())),
             _ => None,
         }
     }
+
+    /// The name of the error variant, used to group errors of the same kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnknownLine(_) => "UnknownLine",
+            Self::UnexpectedNewline => "UnexpectedNewline",
+            Self::ExpectedNewline(_) => "ExpectedNewline",
+            Self::UnexpectedEnd => "UnexpectedEnd",
+            Self::InvalidVersion(_) => "InvalidVersion",
+            Self::InvalidIdNumber(_) => "InvalidIdNumber",
+            Self::InvalidIdHash(_) => "InvalidIdHash",
+            Self::UnknownId(_) => "UnknownId",
+            Self::InvalidVar(_) => "InvalidVar",
+            Self::VarNamesListInconsistent => "VarNamesListInconsistent",
+            Self::VarNamesNoBar => "VarNamesNoBar",
+            Self::UnknownQuantifierIdx(_) => "UnknownQuantifierIdx",
+            Self::QuantNameNoBar(_) => "QuantNameNoBar",
+            Self::QuantNoNumVars(_) => "QuantNoNumVars",
+            Self::NonRewriteAxiomInvalidEnode(_) => "NonRewriteAxiomInvalidEnode",
+            Self::RewriteAxiomMultipleTerms1(_) => "RewriteAxiomMultipleTerms1",
+            Self::RewriteAxiomMultipleTerms2(_) => "RewriteAxiomMultipleTerms2",
+            Self::UnknownInstMethod(_) => "UnknownInstMethod",
+            Self::UnmatchedEndOfInstance => "UnmatchedEndOfInstance",
+            Self::TupleMissingParens => "TupleMissingParens",
+            Self::UnequalTupleForms(..) => "UnequalTupleForms",
+            Self::InvalidFingerprint(_) => "InvalidFingerprint",
+            Self::UnknownFingerprint(_) => "UnknownFingerprint",
+            Self::UnknownEnode(_) => "UnknownEnode",
+            Self::EnodePoppedFrame(_) => "EnodePoppedFrame",
+            Self::InvalidGeneration(_) => "InvalidGeneration",
+            Self::EnodeRootMismatch(..) => "EnodeRootMismatch",
+            Self::StackFrameNotPushed => "StackFrameNotPushed",
+            Self::InvalidFrameInteger(_) => "InvalidFrameInteger",
+            Self::UnknownCheckResult(_) => "UnknownCheckResult",
+            Self::InvalidLiteral(_) => "InvalidLiteral",
+            Self::InvalidResolveLevel(_) => "InvalidResolveLevel",
+            Self::ResolveWithoutConflict => "ResolveWithoutConflict",
+            Self::InvalidSExpr(_) => "InvalidSExpr",
+            Self::UnexpectedInstantiation => "UnexpectedInstantiation",
+            Self::InstArityMismatch(..) => "InstArityMismatch",
+            Self::Allocation(_) => "Allocation",
+        }
+    }
+}
+
+/// Errors from saving or loading a parser
+/// [snapshot](crate::parsers::z3::snapshot).
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    /// The data does not start with the snapshot header.
+    NotASnapshot,
+    /// The snapshot was written with a different format version and must be
+    /// recreated by parsing the original log again.
+    VersionMismatch { found: u32, expected: u32 },
+    Encoding(bincode::Error),
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(err: bincode::Error) -> Self {
+        Self::Encoding(err)
+    }
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "snapshot io error: {err}"),
+            Self::NotASnapshot => write!(f, "not a parser snapshot"),
+            Self::VersionMismatch { found, expected } => write!(
+                f,
+                "snapshot has format version {found} but version {expected} is required, re-parse the original log to create a new snapshot"
+            ),
+            Self::Encoding(err) => write!(f, "invalid snapshot data: {err}"),
+        }
+    }
 }
 
+impl std::error::Error for SnapshotError {}
+
 #[derive(Debug, Clone)]
 pub enum FatalError {
     Allocation(TryReserveError),
+    /// Parsing was aborted by
+    /// [`DiagnosticPolicy::AbortAfter`](crate::DiagnosticPolicy::AbortAfter)
+    /// after this many recoverable errors.
+    TooManyErrors(usize),
 }
@@ -43,6 +43,12 @@ idx!(InstIdx, "i{}");
 idx!(StackIdx, "s{}");
 idx!(ENodeIdx, "e{}");
 idx!(MatchIdx, "m{}");
+idx!(CheckIdx, "c{}");
+idx!(DecisionIdx, "d{}");
+idx!(AssignIdx, "a{}");
+idx!(ConflictIdx, "x{}");
+idx!(CaseSplitIdx, "b{}");
+idx!(ProofIdx, "p{}");
 
 /// A Z3 term and associated data.
 #[derive(Debug, Serialize, Deserialize,PartialEq, Eq, Hash, Clone)]
@@ -102,7 +108,6 @@ pub struct Quantifier {
     pub kind: QuantKind,
     pub num_vars: usize,
     pub term: Option<TermIdx>,
-    pub cost: f32,
     pub instances: Vec<InstIdx>,
     pub vars: Option<VarNames>,
 }
@@ -188,8 +193,11 @@ pub struct Instantiation {
     pub fingerprint: Fingerprint,
     pub proof_id: Option<std::result::Result<TermIdx, TermId>>,
     pub z3_generation: Option<u32>,
-    pub cost: f32,
     pub yields_terms: Box<[ENodeIdx]>,
+    /// The check during which this instantiation happened.
+    pub check: Option<CheckIdx>,
+    /// The innermost case split branch this instantiation happened in.
+    pub case_split: Option<CaseSplitIdx>,
 }
 
 impl Instantiation {
@@ -202,6 +210,8 @@ impl Instantiation {
 pub struct Match {
     pub kind: MatchKind,
     pub blamed: Box<[BlameKind]>,
+    /// The check during which this match was found.
+    pub check: Option<CheckIdx>,
 }
 
 impl Match {
@@ -281,7 +291,8 @@ impl MatchKind {
     pub fn is_mbqi(&self) -> bool {
         matches!(self, Self::MBQI { .. })
     }
-    // TODO: this is currently unused
+    /// For theory-solving rewrites, the term which was rewritten. See
+    /// [`Z3Parser::rewrite_provenance`](crate::Z3Parser::rewrite_provenance).
     pub fn rewrite_of(&self) -> Option<TermIdx> {
         match self {
             Self::TheorySolving { rewrite_of, .. } => *rewrite_of,
@@ -290,6 +301,204 @@ impl MatchKind {
     }
 }
 
+/// A single satisfiability check (e.g. a `(check-sat)` command), delimited by
+/// `[begin-check]` and `[query-done]` in the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Check {
+    /// The depth of the assertion stack when the check began.
+    pub scope: usize,
+    /// `None` if the log ends before the check was done or if the solver did
+    /// not log the result.
+    pub result: Option<CheckResult>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CheckResult {
+    Sat,
+    Unsat,
+    Unknown,
+}
+impl CheckResult {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sat" | "l_true" | "true" => Some(Self::Sat),
+            "unsat" | "l_false" | "false" => Some(Self::Unsat),
+            "unknown" | "l_undef" | "undef" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sat => write!(f, "sat"),
+            Self::Unsat => write!(f, "unsat"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// A boolean literal of the SAT search, logged as `#id` or `(not #id)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Literal {
+    pub term: TermIdx,
+    /// `false` if the literal is negated.
+    pub value: bool,
+    /// The enode of the term when the literal was logged. Its `created_by`
+    /// is the instantiation which produced the literal.
+    pub enode: Option<ENodeIdx>,
+}
+
+/// Why a literal was assigned, the first token after the literal in an
+/// `[assign]` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JustificationKind {
+    Axiom,
+    BinClause,
+    Clause,
+    /// Propagated by a theory solver.
+    Theory,
+    Unknown,
+}
+impl JustificationKind {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "axiom" => Self::Axiom,
+            "bin" | "bin-clause" => Self::BinClause,
+            "clause" => Self::Clause,
+            "justification" => Self::Theory,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Justification {
+    pub kind: JustificationKind,
+    /// The other literals of the clause or theory justification. Tokens which
+    /// are not literals (e.g. theory ids) are skipped.
+    pub lits: Box<[Literal]>,
+}
+
+/// A literal assigned during the search, from an `[assign]` line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    pub lit: Literal,
+    /// Whether the solver logged this assignment as a decision.
+    pub is_decision: bool,
+    pub justification: Justification,
+    /// The innermost decision in effect, `None` at the base level.
+    pub decision: Option<DecisionIdx>,
+}
+
+/// A case split of the search, from a `[decide]` line. Decisions form a tree:
+/// a decision's parent is the innermost earlier decision whose stack frame
+/// was still active, backtracking pops decisions together with their frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decision {
+    pub lit: Literal,
+    pub parent: Option<DecisionIdx>,
+    pub children: Vec<DecisionIdx>,
+    /// The stack frame active when the decision was made.
+    pub frame: Option<StackIdx>,
+    pub check: Option<CheckIdx>,
+    /// The conflicts found while this was the innermost decision.
+    pub conflicts: Vec<ConflictIdx>,
+}
+
+/// A conflict of the search, from a `[conflict]` line, followed by the
+/// conflict resolution steps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conflict {
+    pub lits: Box<[Literal]>,
+    /// The innermost decision in effect, `None` at the base level.
+    pub decision: Option<DecisionIdx>,
+    pub check: Option<CheckIdx>,
+    pub steps: Vec<ResolveStep>,
+}
+impl Conflict {
+    /// All literals which took part in the conflict, including those
+    /// reached during its resolution.
+    pub fn all_lits(&self) -> impl Iterator<Item = &Literal> + '_ {
+        self.lits.iter().chain(self.steps.iter().map(ResolveStep::lit))
+    }
+}
+
+/// A case split on a disjunction (or conjunction), from a `[decide-and-or]`
+/// line. Like decisions, case splits form a tree aligned with the stack
+/// frames they were made in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseSplit {
+    /// The `or` (or `and`) term which was split on.
+    pub term: TermIdx,
+    /// The argument of `term` chosen for this branch.
+    pub branch: TermIdx,
+    pub parent: Option<CaseSplitIdx>,
+    pub children: Vec<CaseSplitIdx>,
+    /// The stack frame active when the split was made.
+    pub frame: Option<StackIdx>,
+    pub check: Option<CheckIdx>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ResolveStep {
+    /// A `[resolve-lit]` line, a literal added to the learned clause. The
+    /// number is logged by the solver before the literal.
+    Lit { level: u32, lit: Literal },
+    /// A `[resolve-process]` line, a literal whose justification is
+    /// resolved.
+    Process(Literal),
+}
+impl ResolveStep {
+    pub fn lit(&self) -> &Literal {
+        match self {
+            Self::Lit { lit, .. } | Self::Process(lit) => lit,
+        }
+    }
+}
+
+/// A step of the proof, from a `[mk-proof]` line. The children of the proof
+/// term are the premises (other proof steps) followed by the conclusion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofStep {
+    /// The proof term of this step.
+    pub term: TermIdx,
+    pub rule: ProofRule,
+    pub premises: Box<[ProofIdx]>,
+    /// The formula proven by this step, `None` if the last child was itself
+    /// a proof.
+    pub conclusion: Option<TermIdx>,
+    /// For `quant-inst` steps, the instantiation whose `proof_id` refers to
+    /// this step or its conclusion. Linked once parsing has completed.
+    pub inst: Option<InstIdx>,
+}
+
+/// The rule applied in a proof step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProofRule {
+    Asserted,
+    Rewrite,
+    Monotonicity,
+    ModusPonens,
+    QuantInst,
+    ThLemma,
+    /// Any other rule, e.g. `trans` or `unit-resolution`.
+    Other(IString),
+}
+impl ProofRule {
+    pub fn parse(strings: &mut StringTable, name: &str) -> Self {
+        match name {
+            "asserted" => Self::Asserted,
+            "rewrite" | "rewrite*" => Self::Rewrite,
+            "monotonicity" => Self::Monotonicity,
+            "mp" | "mp~" => Self::ModusPonens,
+            "quant-inst" => Self::QuantInst,
+            "th-lemma" => Self::ThLemma,
+            _ => Self::Other(strings.get_or_intern(name)),
+        }
+    }
+}
+
 /// The kind of dependency between two quantifier instantiations.
 /// - Term: one instantiation produced a term that the other triggered on
 /// - Equality: dependency based on an equality.
@@ -297,6 +506,9 @@ impl MatchKind {
 pub enum BlameKind {
     Term { term: ENodeIdx },
     Equality { eq: ENodeIdx },
+    /// One side (`term`) of an equality `from = to` derived by a theory
+    /// solver, an axiom or an unknown rule.
+    TheoryEquality { term: ENodeIdx, from: ENodeIdx, to: ENodeIdx, source: EqualitySource },
 }
 
 impl BlameKind {
@@ -304,6 +516,7 @@ impl BlameKind {
         match self {
             Self::Term { term } => Some(*term),
             Self::Equality { eq } => Some(*eq),
+            Self::TheoryEquality { term, .. } => Some(*term),
         }
     }
 }
@@ -364,7 +577,7 @@ impl TermId {
 /// of terms but `TermId`s don't map to this nicely, additionally the `TermId`s
 /// may repeat and so we want to map to the latest current `TermIdx`. Has a
 /// special fast path for the common empty namespace case.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TermIdToIdxMap {
     empty_string: IString,
     empty_namespace: Vec<Option<TermIdx>>,
@@ -475,16 +688,41 @@ impl EqualityExpl {
             | Unknown { to, .. } => to,
         }
     }
-    pub fn dependency_on(&self) -> Option<ENodeIdx> {
+    pub fn dependency_on(&self) -> EqualityDependency<'_> {
         use EqualityExpl::*;
         match *self {
-            Root { .. } => None,
-            Literal { eq, .. } => Some(eq),
-            // TODO: other cases
-            Congruence { .. } => None,
-            Theory { .. } => None,
-            Axiom { .. } => None,
-            Unknown { .. } => None,
+            Root { .. } => EqualityDependency::None,
+            Literal { eq, .. } => EqualityDependency::Literal(eq),
+            Congruence { ref arg_eqs, .. } => EqualityDependency::Congruence(arg_eqs),
+            Theory { from, theory, to } => EqualityDependency::Terms { from, to, source: EqualitySource::Theory(theory) },
+            Axiom { from, to } => EqualityDependency::Terms { from, to, source: EqualitySource::Axiom },
+            Unknown { kind, from, to, .. } => EqualityDependency::Terms { from, to, source: EqualitySource::Unknown(kind) },
         }
     }
 }
+
+/// What the equality of an [`EqualityExpl`] depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqualityDependency<'a> {
+    /// The root of an equivalence class.
+    None,
+    /// An equality literal which was assigned true.
+    Literal(ENodeIdx),
+    /// The equalities between corresponding arguments, each of which is
+    /// explained in turn.
+    Congruence(&'a [(ENodeIdx, ENodeIdx)]),
+    /// Derived by a theory solver, an axiom or an unknown rule. The log does
+    /// not contain the antecedents, so the equality depends on the two terms
+    /// themselves (and whatever created them).
+    Terms { from: ENodeIdx, to: ENodeIdx, source: EqualitySource },
+}
+
+/// Where an equality without logged antecedents comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EqualitySource {
+    /// A theory solver, e.g. `arith` or `bv`.
+    Theory(IString),
+    Axiom,
+    /// An explanation kind this parser does not know about.
+    Unknown(IString),
+}
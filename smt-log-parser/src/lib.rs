@@ -8,7 +8,10 @@ pub mod parsers;
 pub mod display_with;
 
 mod error;
+mod diagnostics;
 
 pub use parsers::z3::z3parser::Z3Parser;
+pub use parsers::cvc5::cvc5parser::Cvc5Parser;
 pub use parsers::LogParser;
-pub use error::{Error, FatalError, Result, FResult};
+pub use error::{Error, FatalError, Result, FResult, SnapshotError};
+pub use diagnostics::{Diagnostic, DiagnosticPolicy, Diagnostics, KindSummary, ParserOptions};
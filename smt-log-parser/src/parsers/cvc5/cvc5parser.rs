@@ -0,0 +1,358 @@
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{
+    items::{BlameKind, CheckResult, MatchKind, QuantIdx, QuantKind, TermIdx, TermKind, VarNames},
+    parsers::LogParser,
+    Error, Result, Z3Parser,
+};
+
+use super::sexpr::{self, SExpr, Token};
+
+/// A parser for cvc5 instantiation traces. The trace consists of the result
+/// of each check followed by one block per quantifier:
+///
+/// ```text
+/// unsat
+/// (instantiations (forall ((x Int)) (! (P x) :pattern ((f x)) :qid q))
+///   (! ( a ) :source QUANTIFIERS_INST_E_MATCHING)
+///   ( b )
+/// )
+/// ```
+///
+/// Instantiations found by e-matching (or of unknown source) of a quantifier
+/// with a single pattern are matches of that pattern, all others are recorded
+/// as [`MatchKind::MBQI`] since they were not triggered by a term. The terms
+/// of an instantiated body which were not seen before are attributed to the
+/// instantiation, this is how dependencies between instantiations are found.
+#[derive(Debug, Default)]
+pub struct Cvc5Parser {
+    pub(super) model: Z3Parser,
+    /// The `(instantiations ...)` or `(skolem ...)` block being parsed.
+    block: Option<Block>,
+    /// Quantifiers by how they were printed, cvc5 prints them again for
+    /// every check.
+    printed: FxHashMap<String, QuantIdx>,
+    /// Quantifiers by their `:qid`, which cvc5 prints instead of the whole
+    /// quantifier with `--print-inst-full=false`.
+    named: FxHashMap<String, QuantIdx>,
+}
+
+#[derive(Debug)]
+enum Block {
+    Instantiations(QuantRef),
+    /// Skolemizations are not instantiations and are skipped.
+    Skolem,
+}
+
+#[derive(Debug, Clone)]
+enum QuantRef {
+    Quant(QuantIdx),
+    /// A quantifier only known by its name, which is created once the number
+    /// of its variables is known from the first instantiation.
+    Name(String),
+}
+
+/// The name of quantifiers without a `:qid`, followed by `!` and their index.
+const UNNAMED_QUANT: &str = "quant";
+
+impl Cvc5Parser {
+    /// The trace read so far, in the same representation as a Z3 log.
+    pub fn model(&self) -> &Z3Parser {
+        &self.model
+    }
+    pub fn into_model(self) -> Z3Parser {
+        self.model
+    }
+
+    pub(super) fn line(&mut self, line: &str) -> Result<()> {
+        let tokens = sexpr::tokenize(line)?;
+        match tokens.as_slice() {
+            [] => Ok(()),
+            [Token::Close] => self.block.take().map(|_| ()).ok_or_else(|| Error::InvalidSExpr(")".to_string())),
+            [Token::Open, Token::Atom("instantiations"), quant @ ..] => {
+                let quant = self.quant_ref(&sexpr::parse_all(quant)?)?;
+                self.block = Some(Block::Instantiations(quant));
+                Ok(())
+            }
+            [Token::Open, Token::Atom("skolem"), ..] => {
+                self.block = Some(Block::Skolem);
+                Ok(())
+            }
+            [Token::Atom(result)] => {
+                let result = CheckResult::parse(result).ok_or_else(|| Error::UnknownLine(result.to_string()))?;
+                self.model.import_check(result)
+            }
+            [Token::Open, ..] if self.block.is_some() => {
+                let terms = sexpr::parse_all(&tokens)?;
+                match &self.block {
+                    Some(Block::Instantiations(quant)) => self.instantiation(quant.clone(), &terms),
+                    _ => Ok(()),
+                }
+            }
+            [Token::Open, Token::Open | Token::Close | Token::Atom("!"), ..] => Err(Error::UnexpectedInstantiation),
+            [Token::Open, Token::Atom(first), ..] => Err(Error::UnknownLine(format!("({first}"))),
+            _ => Err(Error::UnknownLine(line.to_string())),
+        }
+    }
+
+    pub(super) fn eof(&mut self) {
+        self.block = None;
+        self.model.end_of_file();
+    }
+
+    fn quant_ref(&mut self, quant: &SExpr) -> Result<QuantRef> {
+        if let Some(name) = quant.symbol() {
+            return Ok(self.named.get(name).map_or_else(|| QuantRef::Name(name.to_string()), |&q| QuantRef::Quant(q)));
+        }
+        let printed = quant.to_string();
+        if let Some(&quant) = self.printed.get(&printed) {
+            return Ok(QuantRef::Quant(quant));
+        }
+        let qidx = self.quantifier(quant, &mut Vec::new(), &mut Vec::new())?;
+        self.printed.try_reserve(1)?;
+        self.printed.insert(printed, qidx);
+        Ok(QuantRef::Quant(qidx))
+    }
+
+    fn app(&mut self, name: &str, children: Vec<TermIdx>) -> TermIdx {
+        let name = self.model.strings.get_or_intern(name);
+        self.model.import_term(TermKind::parse_proof_app(false, name), children)
+    }
+
+    /// Convert an s-expression to a term. `bound` are the names of the
+    /// variables of the enclosing quantifiers (outermost first) and `lets`
+    /// the enclosing `let` bindings.
+    fn term(&mut self, e: &SExpr, bound: &mut Vec<String>, lets: &mut Vec<(String, TermIdx)>) -> Result<TermIdx> {
+        let Some(list) = e.list() else {
+            let name = e.symbol().unwrap();
+            if let Some((_, term)) = lets.iter().rev().find(|(n, _)| n == name) {
+                return Ok(*term);
+            }
+            if let Some(var) = bound.iter().rposition(|n| n == name) {
+                return Ok(self.model.import_term(TermKind::Var(var), Vec::new()));
+            }
+            return Ok(self.app(name, Vec::new()));
+        };
+        match list {
+            [binder, _, _] if binder.is_atom("forall") || binder.is_atom("exists") => {
+                let quant = self.quantifier(e, bound, lets)?;
+                Ok(self.model[quant].term.unwrap())
+            }
+            [binder, bindings, body] if binder.is_atom("let") => {
+                let bindings = bindings.list().ok_or_else(|| Error::InvalidSExpr(e.to_string()))?;
+                let mut values = Vec::new();
+                for binding in bindings {
+                    let Some([name, value]) = binding.list() else {
+                        return Err(Error::InvalidSExpr(binding.to_string()));
+                    };
+                    let name = name.symbol().ok_or_else(|| Error::InvalidSExpr(binding.to_string()))?;
+                    values.try_reserve(1)?;
+                    values.push((name.to_string(), self.term(value, bound, lets)?));
+                }
+                // The bindings of a `let` are parallel, they are only visible
+                // in the body.
+                let outer = lets.len();
+                lets.try_reserve(values.len())?;
+                lets.extend(values);
+                let body = self.term(body, bound, lets);
+                lets.truncate(outer);
+                body
+            }
+            [annotated, body, ..] if annotated.is_atom("!") => self.term(body, bound, lets),
+            [indexed, ..] if indexed.is_atom("_") => Ok(self.app(&e.to_string(), Vec::new())),
+            [head, args @ ..] if !args.is_empty() => {
+                let name = head.symbol().map_or_else(|| head.to_string(), str::to_string);
+                let children = args.iter().map(|a| self.term(a, bound, lets)).collect::<Result<Vec<_>>>()?;
+                Ok(self.app(&name, children))
+            }
+            _ => Err(Error::InvalidSExpr(e.to_string())),
+        }
+    }
+
+    fn quantifier(&mut self, e: &SExpr, bound: &mut Vec<String>, lets: &mut Vec<(String, TermIdx)>) -> Result<QuantIdx> {
+        let Some([_, vars, body]) = e.list() else {
+            return Err(Error::InvalidSExpr(e.to_string()));
+        };
+        let vars = vars
+            .list()
+            .ok_or_else(|| Error::InvalidSExpr(vars.to_string()))?
+            .iter()
+            .map(|var| match var.list() {
+                Some([name, sort]) if name.symbol().is_some() => Ok((name.symbol().unwrap(), sort.to_string())),
+                _ => Err(Error::InvalidSExpr(var.to_string())),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (body, attributes) = match body.list() {
+            Some([annotated, body, attributes @ ..]) if annotated.is_atom("!") => (body, attributes),
+            _ => (body, &[][..]),
+        };
+        let qid = attributes
+            .windows(2)
+            .find(|attr| attr[0].is_atom(":qid"))
+            .and_then(|attr| attr[1].symbol());
+
+        let outer = bound.len();
+        bound.try_reserve(vars.len())?;
+        bound.extend(vars.iter().map(|(name, _)| name.to_string()));
+        let children = self.quant_children(body, attributes, bound, lets);
+        bound.truncate(outer);
+        let children = children?;
+
+        let kind = match qid {
+            Some(qid) => QuantKind::parse(&mut self.model.strings, qid),
+            None => QuantKind::UnnamedQuant {
+                name: self.model.strings.get_or_intern_static(UNNAMED_QUANT),
+                id: self.model.quant_count_incl_theory_solving().0,
+            },
+        };
+        let num_vars = vars.len();
+        let vars = vars
+            .into_iter()
+            .map(|(name, sort)| (self.model.strings.get_or_intern(name), self.model.strings.get_or_intern(sort)))
+            .collect();
+        let quant = self.model.import_quant(kind, Some(VarNames::NameAndType(vars)), num_vars, Some(children))?;
+        if let Some(qid) = qid {
+            self.named.try_reserve(1)?;
+            self.named.insert(qid.to_string(), quant);
+        }
+        Ok(quant)
+    }
+
+    /// The patterns of a quantifier followed by its body.
+    fn quant_children(
+        &mut self,
+        body: &SExpr,
+        attributes: &[SExpr],
+        bound: &mut Vec<String>,
+        lets: &mut Vec<(String, TermIdx)>,
+    ) -> Result<Vec<TermIdx>> {
+        let mut children = Vec::new();
+        for attr in attributes.windows(2).filter(|attr| attr[0].is_atom(":pattern")) {
+            let terms = attr[1].list().ok_or_else(|| Error::InvalidSExpr(attr[1].to_string()))?;
+            let terms = terms.iter().map(|t| self.term(t, bound, lets)).collect::<Result<Vec<_>>>()?;
+            children.try_reserve(1)?;
+            children.push(self.app("pattern", terms));
+        }
+        children.try_reserve(1)?;
+        children.push(self.term(body, bound, lets)?);
+        Ok(children)
+    }
+
+    fn instantiation(&mut self, quant: QuantRef, e: &SExpr) -> Result<()> {
+        let list = e.list().unwrap();
+        let (terms, attributes) = match list {
+            [annotated, terms, attributes @ ..] if annotated.is_atom("!") => {
+                (terms.list().ok_or_else(|| Error::InvalidSExpr(e.to_string()))?, attributes)
+            }
+            terms => (terms, &[][..]),
+        };
+        // `:source <inference id> [<trigger>]`
+        let source = attributes.iter().position(|attr| attr.is_atom(":source"));
+        let source = source.map(|idx| &attributes[idx + 1..]).unwrap_or_default();
+        let is_ematching = source.first().and_then(SExpr::symbol).is_none_or(|s| s.contains("E_MATCHING"));
+
+        let bound_terms = terms
+            .iter()
+            .map(|t| self.term(t, &mut Vec::new(), &mut Vec::new()))
+            .collect::<Result<Vec<_>>>()?;
+        let quant = match quant {
+            QuantRef::Quant(quant) => quant,
+            QuantRef::Name(name) => {
+                let kind = QuantKind::Other(self.model.strings.get_or_intern(&name));
+                let quant = self.model.import_quant(kind, None, bound_terms.len(), None)?;
+                self.named.try_reserve(1)?;
+                self.named.insert(name, quant);
+                self.block = Some(Block::Instantiations(QuantRef::Quant(quant)));
+                quant
+            }
+        };
+        let num_vars = self.model[quant].num_vars;
+        if bound_terms.len() != num_vars {
+            return Err(Error::InstArityMismatch(num_vars, bound_terms.len()));
+        }
+
+        let children = self.model[quant].term.map(|t| self.model[t].child_ids.to_vec()).unwrap_or_default();
+        let (body, patterns) = children.split_last().map_or((None, &[][..]), |(body, patterns)| (Some(*body), patterns));
+        let pattern = match (source.get(1), patterns) {
+            _ if !is_ematching => None,
+            (Some(trigger), _) => {
+                let mut vars = self.var_names(quant);
+                let trigger = self.term(trigger, &mut vars, &mut Vec::new())?;
+                Some(self.app("pattern", vec![trigger]))
+            }
+            (None, &[pattern]) => Some(pattern),
+            (None, _) => None,
+        };
+
+        let mut substituted = FxHashMap::default();
+        let bound = bound_terms.iter().map(|&t| self.model.import_enode(t)).collect::<Result<Vec<_>>>()?;
+        let (kind, blamed) = match pattern {
+            Some(pattern) => {
+                let triggers = self.model[pattern].child_ids.to_vec();
+                let mut blamed = Vec::new();
+                for trigger in triggers {
+                    let trigger = self.substitute(trigger, &bound_terms, &mut substituted);
+                    blamed.try_reserve(1)?;
+                    blamed.push(BlameKind::Term { term: self.model.import_enode(trigger)? });
+                }
+                (MatchKind::Quantifier { quant, pattern, bound_terms: bound }, blamed)
+            }
+            None => (MatchKind::MBQI { quant, bound_terms: bound }, Vec::new()),
+        };
+        let result = body.map(|body| self.substitute(body, &bound_terms, &mut substituted));
+        let mut yields = Vec::new();
+        if let Some(result) = result {
+            self.subterms(result, &mut yields, &mut FxHashSet::default());
+        }
+        self.model.import_inst(kind, blamed, result, yields)?;
+        Ok(())
+    }
+
+    fn var_names(&self, quant: QuantIdx) -> Vec<String> {
+        let quant = &self.model[quant];
+        (0..quant.num_vars).map(|idx| VarNames::get_name(&self.model.strings, &quant.vars, idx).into_owned()).collect()
+    }
+
+    /// Replace the variables of the outermost quantifier in `term` with
+    /// `bound`, the variables of nested quantifiers are shifted accordingly.
+    fn substitute(&mut self, term: TermIdx, bound: &[TermIdx], done: &mut FxHashMap<TermIdx, TermIdx>) -> TermIdx {
+        if let Some(&substituted) = done.get(&term) {
+            return substituted;
+        }
+        let kind = self.model[term].kind;
+        let substituted = match kind {
+            TermKind::Var(idx) if idx < bound.len() => bound[idx],
+            TermKind::Var(idx) => self.model.import_term(TermKind::Var(idx - bound.len()), Vec::new()),
+            _ if self.model[term].child_ids.is_empty() => term,
+            _ => {
+                let children = self.model[term].child_ids.to_vec();
+                let children = children.into_iter().map(|c| self.substitute(c, bound, done)).collect();
+                self.model.import_term(kind, children)
+            }
+        };
+        done.insert(term, substituted);
+        substituted
+    }
+
+    /// The subterms of `term` (children first), without looking into nested
+    /// quantifiers since their terms are not part of the e-graph.
+    fn subterms(&self, term: TermIdx, out: &mut Vec<TermIdx>, seen: &mut FxHashSet<TermIdx>) {
+        if !seen.insert(term) {
+            return;
+        }
+        let t = &self.model[term];
+        if matches!(t.kind, TermKind::Quant(_) | TermKind::Var(_)) {
+            return;
+        }
+        for &child in t.child_ids.iter() {
+            self.subterms(child, out, seen);
+        }
+        out.push(term);
+    }
+}
+
+impl From<Cvc5Parser> for Z3Parser {
+    fn from(parser: Cvc5Parser) -> Self {
+        parser.into_model()
+    }
+}
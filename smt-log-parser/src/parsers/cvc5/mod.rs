@@ -0,0 +1,35 @@
+use crate::{FResult, ParserOptions};
+use super::{z3::Z3LogParser, LogParser};
+
+/// Parser for the quantifier instantiations which cvc5 prints with
+/// `--dump-instantiations` (add `--dump-instantiations-debug` to also get
+/// the source of each instantiation). The trace is read into a
+/// [`Z3Parser`](crate::Z3Parser) so that everything built on top of it works
+/// the same for both solvers.
+pub mod cvc5parser;
+mod sexpr;
+
+use cvc5parser::Cvc5Parser;
+
+impl LogParser for Cvc5Parser {
+    fn set_options(&mut self, options: ParserOptions) {
+        self.model.diagnostics_mut().set_policy(options.diagnostics);
+    }
+
+    fn process_line(&mut self, line: &str, line_no: usize, byte_offset: usize) -> FResult<bool> {
+        match self.line(line) {
+            Ok(()) => Ok(true),
+            Err(err) => match err.as_fatal() {
+                Some(err) => Err(err),
+                None => {
+                    self.model.diagnostics_mut().report(line_no, byte_offset, line, err)?;
+                    Ok(true)
+                }
+            },
+        }
+    }
+
+    fn end_of_file(&mut self) {
+        self.eof();
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+
+use crate::{Error, Result};
+
+/// An SMT-LIB s-expression as printed by cvc5. Atoms keep the bars of quoted
+/// symbols, use [`SExpr::symbol`] to get the symbol itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>),
+}
+
+impl SExpr {
+    /// The symbol of an atom without the surrounding bars (e.g. `|a b|` is the
+    /// symbol `a b`).
+    pub(super) fn symbol(&self) -> Option<&str> {
+        match self {
+            Self::Atom(atom) => Some(atom.strip_prefix('|').and_then(|a| a.strip_suffix('|')).unwrap_or(atom)),
+            Self::List(_) => None,
+        }
+    }
+    pub(super) fn list(&self) -> Option<&[SExpr]> {
+        match self {
+            Self::Atom(_) => None,
+            Self::List(list) => Some(list),
+        }
+    }
+    pub(super) fn is_atom(&self, atom: &str) -> bool {
+        matches!(self, Self::Atom(a) if a == atom)
+    }
+}
+
+impl fmt::Display for SExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Atom(atom) => write!(f, "{atom}"),
+            Self::List(list) => {
+                write!(f, "(")?;
+                for (idx, e) in list.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{e}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Token<'a> {
+    Open,
+    Close,
+    Atom(&'a str),
+}
+
+impl<'a> Token<'a> {
+    fn text(&self) -> &'a str {
+        match self {
+            Self::Open => "(",
+            Self::Close => ")",
+            Self::Atom(atom) => atom,
+        }
+    }
+}
+
+/// Split a line into tokens, stopping at a `;` comment. Quoted symbols
+/// (`|...|`) and string literals (`"..."`) may contain spaces and parentheses
+/// but must end on the same line.
+pub(super) fn tokenize(line: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let bytes = line.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let start = idx;
+        let token = match bytes[idx] {
+            b if b.is_ascii_whitespace() => {
+                idx += 1;
+                continue;
+            }
+            b';' => break,
+            b'(' => {
+                idx += 1;
+                Token::Open
+            }
+            b')' => {
+                idx += 1;
+                Token::Close
+            }
+            quote @ (b'|' | b'"') => {
+                idx += 1;
+                loop {
+                    match bytes.get(idx) {
+                        None => return Err(Error::InvalidSExpr(line[start..].to_string())),
+                        // `""` is an escaped quote within a string literal.
+                        Some(b'"') if quote == b'"' && bytes.get(idx + 1) == Some(&b'"') => idx += 2,
+                        Some(&b) if b == quote => break,
+                        Some(_) => idx += 1,
+                    }
+                }
+                idx += 1;
+                Token::Atom(&line[start..idx])
+            }
+            _ => {
+                while idx < bytes.len() && !matches!(bytes[idx], b'(' | b')' | b';' | b'|' | b'"') && !bytes[idx].is_ascii_whitespace() {
+                    idx += 1;
+                }
+                Token::Atom(&line[start..idx])
+            }
+        };
+        tokens.try_reserve(1)?;
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Parse a single s-expression from the start of `tokens`, returns it and the
+/// remaining tokens.
+pub(super) fn parse<'a, 'b>(tokens: &'b [Token<'a>]) -> Result<(SExpr, &'b [Token<'a>])> {
+    let (first, mut rest) = tokens.split_first().ok_or(Error::UnexpectedEnd)?;
+    match *first {
+        Token::Atom(atom) => Ok((SExpr::Atom(atom.to_string()), rest)),
+        Token::Close => Err(Error::InvalidSExpr(")".to_string())),
+        Token::Open => {
+            let mut list = Vec::new();
+            loop {
+                match rest.first() {
+                    None => return Err(Error::UnexpectedEnd),
+                    Some(Token::Close) => return Ok((SExpr::List(list), &rest[1..])),
+                    Some(_) => {
+                        let (e, remaining) = parse(rest)?;
+                        list.try_reserve(1)?;
+                        list.push(e);
+                        rest = remaining;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parse `tokens` which must form exactly one s-expression.
+pub(super) fn parse_all(tokens: &[Token<'_>]) -> Result<SExpr> {
+    let (e, rest) = parse(tokens)?;
+    match rest.first() {
+        Some(more) => Err(Error::ExpectedNewline(more.text().to_string())),
+        None => Ok(e),
+    }
+}
@@ -1,5 +1,6 @@
 use crate::FResult;
 use crate::FatalError;
+use crate::ParserOptions;
 
 pub use self::wrapper_async_parser::*;
 pub use self::wrapper_stream_parser::*;
@@ -11,6 +12,7 @@ use std::path::Path;
 use std::time::Duration;
 use wasm_timer::Instant;
 
+pub mod cvc5;
 pub mod z3;
 
 /// Trait for a generic SMT solver trace parser. Intended to support different
@@ -21,9 +23,15 @@ pub trait LogParser: Default {
         true
     }
 
-    /// Process a single line of the log file. Return `true` if parsing should
-    /// continue, or `false` if parsing should stop.
-    fn process_line(&mut self, line: &str, line_no: usize) -> FResult<bool>;
+    /// Configure how the parser behaves, e.g. what to do with recoverable
+    /// errors. Should be called before parsing starts.
+    fn set_options(&mut self, options: ParserOptions);
+
+    /// Process a single line of the log file. The `line_no` starts from 1 and
+    /// `byte_offset` is the position of the start of the line in the input.
+    /// Return `true` if parsing should continue, or `false` if parsing should
+    /// stop.
+    fn process_line(&mut self, line: &str, line_no: usize, byte_offset: usize) -> FResult<bool>;
 
     fn end_of_file(&mut self);
 
@@ -145,6 +153,9 @@ impl<T: AsRef<[u8]> + Unpin> AsyncCursorRead for T {}
 #[derive(Debug, Clone)]
 pub enum ParseState {
     Paused(ReaderState),
+    /// All of the input written so far has been parsed, more may follow. Only
+    /// returned when [following](StreamParser::follow) the input.
+    Waiting(ReaderState),
     Completed { end_of_stream: bool },
     Error(FatalError),
 }
@@ -181,6 +192,11 @@ mod wrapper {
         reader: Option<Box<dyn ReadBound>>,
         reader_state: ReaderState,
         parser: Parser,
+        follow: bool,
+        /// The (possibly partial) line read before we last reached the end
+        /// of a followed input. Kept as bytes since a partially written line
+        /// may end in the middle of a UTF-8 character.
+        pending: Vec<u8>,
     }
     impl<'r, Parser: LogParser, R: ReadBound> From<R> for EitherParser<'r, Parser> {
         fn from(reader: R) -> Self {
@@ -193,9 +209,34 @@ mod wrapper {
                 reader: Some(Box::new(reader)),
                 reader_state: ReaderState::default(),
                 parser: Parser::default(),
+                follow: false,
+                pending: Vec::new(),
             }
         }
 
+        /// Set the options of the underlying parser, see
+        /// [`LogParser::set_options`].
+        pub fn with_options(mut self, options: ParserOptions) -> Self {
+            self.parser.set_options(options);
+            self
+        }
+        /// Follow the input as it is being written, e.g. the log of a solver
+        /// which is still running. Instead of stopping at the end of the
+        /// input, parsing returns `ParseState::Waiting` and can be resumed
+        /// with [`process_available`](Self::process_available) once more has
+        /// been written. A line is only parsed once the start
+        /// of the next one has been read, so that partially written (or
+        /// multi-) lines are never parsed early. Call
+        /// [`stop_following`](Self::stop_following) once the writer is done.
+        pub fn follow(mut self) -> Self {
+            self.follow = true;
+            self
+        }
+        /// Are we following the input? See [`follow`](Self::follow).
+        pub fn is_following(&self) -> bool {
+            self.follow
+        }
+
         /// Get the current parser state.
         pub fn parser(&self) -> &Parser {
             &self.parser
@@ -230,24 +271,31 @@ mod wrapper {
             let Some(reader) = self.reader.as_mut() else {
                 return ParseState::Completed { end_of_stream: true };
             };
-            let mut buf = String::new();
+            let buf = &mut self.pending;
             while predicate(&self.parser, self.reader_state) {
-                buf.clear();
-                // Read line
-                let mut bytes_read = 0;
-
+                // Read line, the start of which may have already been read
+                // before we last reached the end of a followed input.
+                let mut read_more = !buf.ends_with(b"\n");
                 loop {
-                    bytes_read += add_await([reader.read_line(&mut buf)]).unwrap();
+                    if read_more {
+                        add_await([reader.read_until(b'\n', buf)]).unwrap();
+                    }
+                    read_more = true;
                     let peek = add_await([reader.fill_buf()]).unwrap();
+                    if peek.is_empty() && self.follow {
+                        // The line may not have been completely written yet.
+                        return ParseState::Waiting(self.reader_state);
+                    }
                     // Stop reading if this is the end or we don't have a multiline.
                     if peek.is_empty() || self.parser.is_line_start(peek[0]) {
                         break;
                     }
                 }
+                let bytes_read = buf.len();
                 // Remove newline from end
-                if buf.ends_with('\n') {
+                if buf.ends_with(b"\n") {
                     buf.pop();
-                    if buf.ends_with('\r') {
+                    if buf.ends_with(b"\r") {
                         buf.pop();
                     }
                 }
@@ -255,9 +303,13 @@ mod wrapper {
                 let state = if bytes_read == 0 {
                     Some(ParseState::Completed { end_of_stream: true })
                 } else {
+                    let byte_offset = self.reader_state.bytes_read;
                     self.reader_state.bytes_read += bytes_read;
                     self.reader_state.lines_read += 1;
-                    match self.parser.process_line(&buf, self.reader_state.lines_read) {
+                    // The line is complete, so only invalid UTF-8 in the log
+                    // itself is replaced.
+                    let line = String::from_utf8_lossy(buf);
+                    match self.parser.process_line(&line, self.reader_state.lines_read, byte_offset) {
                         Ok(true) => None,
                         Ok(false) =>
                             Some(ParseState::Completed { end_of_stream: false }),
@@ -265,6 +317,7 @@ mod wrapper {
                             Some(ParseState::Error(err)),
                     }
                 };
+                buf.clear();
                 if let Some(state) = state {
                     drop(self.reader.take()); // Release file handle/free up memory
                     self.parser.end_of_file();
@@ -353,8 +406,10 @@ mod wrapper {
         /// instead is recommended as this method will cause the process to hang
         /// if given a very large file.
         pub async fn process_all(mut self) -> FResult<Parser> {
+            // Everything written so far is all there is.
+            self.follow = false;
             match add_await([self.process_until(|_, _| true)]) {
-                ParseState::Paused(_) => unreachable!(),
+                ParseState::Paused(_) | ParseState::Waiting(_) => unreachable!(),
                 ParseState::Completed { .. } => Ok(self.parser),
                 ParseState::Error(err) => Err(err),
             }
@@ -365,14 +420,48 @@ mod wrapper {
         /// only if `ParseState::Completed` was returned).
         ///
         /// Parsing cannot be resumed if the timeout is reached. If you need
-        /// support for resuming, use [`process_check_every`] or
-        /// [`process_until`] instead.
+        /// support for resuming, use [`process_for`](Self::process_for)
+        /// instead.
         pub async fn process_all_timeout(
             mut self,
             timeout: Duration,
         ) -> (ParseState, Parser) {
-            let result = add_await([self.process_check_every(timeout, |_, _| false)]);
+            let result = add_await([self.process_for(timeout)]);
             (result, self.parser)
         }
+        /// Try to parse everything, but pause after a given timeout. Unlike
+        /// [`process_all_timeout`](Self::process_all_timeout) this keeps the
+        /// reader, so if `ParseState::Paused` is returned the partial state
+        /// can be inspected with [`parser`](Self::parser) (e.g. to build an
+        /// `InstGraph`) and parsing resumed by calling this method again. This
+        /// can be repeated any number of times until `ParseState::Completed`
+        /// is returned.
+        ///
+        /// Note that end-of-file processing (such as computing instantiation
+        /// costs) only happens once the input is exhausted, the partial state
+        /// does not include it.
+        pub async fn process_for(&mut self, timeout: Duration) -> ParseState {
+            add_await([self.process_check_every(timeout, |_, _| false)])
+        }
+        /// Parse everything written so far to a [followed](Self::follow)
+        /// input, returning `ParseState::Waiting` once done. The caller
+        /// decides when to call this again, e.g. after sleeping or awaiting
+        /// a notification that the file changed, and can in between
+        /// [`extend`](crate::parsers::z3::inst_graph::InstGraph::extend) an
+        /// `InstGraph` with the new instantiations.
+        ///
+        /// As with [`process_for`](Self::process_for), end-of-file processing
+        /// only happens once parsing completes, i.e. after
+        /// [`stop_following`](Self::stop_following).
+        pub async fn process_available(&mut self) -> ParseState {
+            add_await([self.process_until(|_, _| true)])
+        }
+        /// Stop [following](Self::follow) the input, e.g. once the solver
+        /// writing it has exited, and parse the remainder including any
+        /// partially written last line.
+        pub async fn stop_following(&mut self) -> ParseState {
+            self.follow = false;
+            add_await([self.process_until(|_, _| true)])
+        }
     }
 }
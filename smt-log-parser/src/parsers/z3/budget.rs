@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::items::{QuantIdx, QuantKind};
+
+use super::{inst_graph::InstGraph, z3parser::Z3Parser};
+
+/// Limits on the instantiations of quantifiers, e.g. to catch performance
+/// regressions of a proof in CI. See [`Budget::check`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    /// The limits of quantifiers by name. The name of an unnamed `name!id`
+    /// quantifier is also matched without the `!id` suffix, since the ids
+    /// change between runs. An exact match takes precedence.
+    #[serde(default)]
+    pub quantifiers: BTreeMap<String, QuantBudget>,
+    /// The limits of quantifiers which match none of the names.
+    #[serde(default)]
+    pub default: Option<QuantBudget>,
+}
+
+/// The limits of a single quantifier, unset limits are not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuantBudget {
+    /// The number of instantiations.
+    pub insts: Option<usize>,
+    /// The summed cost of all instantiations, under the cost model of the
+    /// graph.
+    pub cost: Option<f32>,
+    /// The highest Z3 generation of any instantiation.
+    pub max_generation: Option<u32>,
+    /// The length of the longest chain of instantiations, see
+    /// [`InstGraph::quant_chains`].
+    pub loop_length: Option<usize>,
+}
+
+impl Budget {
+    /// The limits which apply to a quantifier, along with the name they were
+    /// given for (`None` for the [`default`](Self::default)).
+    pub fn limits_for<'a>(&'a self, parser: &Z3Parser, quant: QuantIdx) -> Option<(Option<&'a str>, &'a QuantBudget)> {
+        let exact = self.quantifiers.get_key_value(&parser.quant_name(quant));
+        let prefix = || match &parser.quantifiers[quant].kind {
+            QuantKind::UnnamedQuant { name, .. } => self.quantifiers.get_key_value(&parser.strings[*name]),
+            _ => None,
+        };
+        match exact.or_else(prefix) {
+            Some((name, limits)) => Some((Some(name.as_str()), limits)),
+            None => self.default.as_ref().map(|limits| (None, limits)),
+        }
+    }
+
+    /// Check every quantifier against its limits, returning the exceeded
+    /// ones in quantifier order. Costs are computed under the cost model of
+    /// the `graph`. Searching for loops resets the visibility of its nodes,
+    /// this is only done if a loop length limit is set.
+    pub fn check(&self, parser: &Z3Parser, graph: &mut InstGraph) -> Vec<Violation> {
+        let stats = parser.quant_stats(&parser.inst_costs(&graph.cost_model()));
+        let limits_loops = self.quantifiers.values().chain(&self.default).any(|limits| limits.loop_length.is_some());
+        let quant_chains = if limits_loops { graph.quant_chains() } else { Default::default() };
+        let mut violations = Vec::new();
+        for (quant, stats) in stats.iter_enumerated() {
+            let Some((budget, limits)) = self.limits_for(parser, quant) else {
+                continue;
+            };
+            let exceeded = [
+                limits.insts.filter(|&limit| stats.insts > limit).map(|limit| Exceeded::Insts { limit, actual: stats.insts }),
+                limits.cost.filter(|&limit| stats.cost > limit).map(|limit| Exceeded::Cost { limit, actual: stats.cost }),
+                limits
+                    .max_generation
+                    .zip(stats.max_generation)
+                    .filter(|&(limit, actual)| actual > limit)
+                    .map(|(limit, actual)| Exceeded::MaxGeneration { limit, actual }),
+                limits
+                    .loop_length
+                    .zip(quant_chains.get(&quant).map(|chains| chains.longest))
+                    .filter(|&(limit, actual)| actual > limit)
+                    .map(|(limit, actual)| Exceeded::LoopLength { limit, actual }),
+            ];
+            violations.extend(exceeded.into_iter().flatten().map(|exceeded| Violation {
+                quant,
+                name: parser.quant_name(quant),
+                budget: budget.map(str::to_string),
+                exceeded,
+            }));
+        }
+        violations
+    }
+}
+
+/// A quantifier which exceeded one of its limits.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    #[serde(skip)]
+    pub quant: QuantIdx,
+    pub name: String,
+    /// The name in the [`Budget`] which the quantifier matched, `None` if the
+    /// default limits applied.
+    pub budget: Option<String>,
+    #[serde(flatten)]
+    pub exceeded: Exceeded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "exceeded", rename_all = "snake_case")]
+pub enum Exceeded {
+    Insts { limit: usize, actual: usize },
+    Cost { limit: f32, actual: f32 },
+    MaxGeneration { limit: u32, actual: u32 },
+    LoopLength { limit: usize, actual: usize },
+}
+
+impl fmt::Display for Exceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insts { limit, actual } => write!(f, "{actual} instantiations exceed the limit of {limit}"),
+            Self::Cost { limit, actual } => write!(f, "a cost of {actual:.1} exceeds the limit of {limit:.1}"),
+            Self::MaxGeneration { limit, actual } => write!(f, "generation {actual} exceeds the limit of {limit}"),
+            Self::LoopLength { limit, actual } => {
+                write!(f, "a chain of {actual} instantiations exceeds the loop length limit of {limit}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.budget {
+            Some(budget) if *budget != self.name => write!(f, "{} (budget for {budget}): {}", self.name, self.exceeded),
+            Some(_) => write!(f, "{}: {}", self.name, self.exceeded),
+            None => write!(f, "{} (default budget): {}", self.name, self.exceeded),
+        }
+    }
+}
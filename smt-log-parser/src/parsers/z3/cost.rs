@@ -0,0 +1,235 @@
+use std::fmt;
+
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use typed_index_collections::TiVec;
+
+use crate::items::{InstIdx, Instantiation, QuantIdx};
+
+use super::z3parser::Z3Parser;
+
+/// A way of attributing a cost to each instantiation, used to find the
+/// instantiations (and quantifiers) most responsible for a slow or failing
+/// run. Costs are computed on demand from the instantiations parsed so far,
+/// see [`Z3Parser::inst_costs`].
+pub trait CostModel {
+    /// A short kebab-case name of the model.
+    fn name(&self) -> &'static str;
+    fn costs(&self, parser: &Z3Parser) -> Costs;
+}
+
+/// The built-in cost models.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CostKind {
+    /// Each instantiation costs 1, plus the costs of the instantiations which
+    /// depend on it. An instantiation splits its cost evenly among the
+    /// instantiations it depends on.
+    #[default]
+    SplitShare,
+    /// The number of instantiations which depend on an instantiation,
+    /// directly or indirectly, including itself.
+    InclusiveSubtree,
+    /// The number of instantiations which could not have happened without an
+    /// instantiation, i.e. which depend on it along every path, including
+    /// itself.
+    ExclusiveSubtree,
+    /// As [`SplitShare`](Self::SplitShare), but each instantiation costs 1
+    /// plus the number of terms it yields.
+    YieldsWeighted,
+    /// As [`SplitShare`](Self::SplitShare), but each instantiation costs 1
+    /// plus its generation, emphasising long chains of instantiations.
+    GenerationWeighted,
+}
+
+impl CostKind {
+    pub const ALL: [Self; 5] = [
+        Self::SplitShare,
+        Self::InclusiveSubtree,
+        Self::ExclusiveSubtree,
+        Self::YieldsWeighted,
+        Self::GenerationWeighted,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Each instantiation costs its own weight plus a share of the cost of
+    /// every instantiation which depends on it.
+    fn split_share(parser: &Z3Parser, weight: impl Fn(&Instantiation) -> f32) -> Costs {
+        let mut costs: TiVec<InstIdx, f32> = parser.insts.insts.iter().map(weight).collect();
+        // Instantiations only depend on earlier ones, so walking backwards
+        // visits each instantiation after everything which depends on it.
+        for (idx, inst) in parser.insts.insts.iter_enumerated().rev() {
+            let deps: Vec<_> = parents(parser, inst).collect();
+            let share = costs[idx] / deps.len() as f32;
+            for dep in deps {
+                costs[dep] += share;
+            }
+        }
+        Costs { costs, split: true }
+    }
+
+    fn inclusive_subtree(parser: &Z3Parser) -> Costs {
+        let mut costs: TiVec<InstIdx, f32> = parser.insts.insts.iter().map(|_| 0.0).collect();
+        // The descendants found so far of each instantiation. As with
+        // `split_share`, walking backwards completes the descendants of an
+        // instantiation before it is visited, after which they are only
+        // needed by its parents.
+        let mut descendants: TiVec<InstIdx, RoaringBitmap> = parser.insts.insts.iter().map(|_| RoaringBitmap::new()).collect();
+        for (idx, inst) in parser.insts.insts.iter_enumerated().rev() {
+            let mut own = std::mem::take(&mut descendants[idx]);
+            own.insert(usize::from(idx) as u32);
+            costs[idx] = own.len() as f32;
+            for parent in parents(parser, inst) {
+                descendants[parent] |= &own;
+            }
+        }
+        Costs { costs, split: false }
+    }
+
+    fn exclusive_subtree(parser: &Z3Parser) -> Costs {
+        // Build the dominator tree of the instantiations (with a virtual root
+        // above all of those which depend on nothing): since parents come
+        // first, the immediate dominator of each instantiation is the
+        // nearest common dominator of its parents.
+        let mut idom: TiVec<InstIdx, Option<InstIdx>> = TiVec::with_capacity(parser.insts.insts.len());
+        let mut depth: TiVec<InstIdx, usize> = TiVec::with_capacity(parser.insts.insts.len());
+        for inst in parser.insts.insts.iter() {
+            let mut parents = parents(parser, inst);
+            let dom = parents.next().and_then(|first| {
+                parents.try_fold(first, |mut a, mut b| {
+                    while a != b {
+                        if depth[a] < depth[b] {
+                            std::mem::swap(&mut a, &mut b);
+                        }
+                        a = idom[a]?;
+                    }
+                    Some(a)
+                })
+            });
+            depth.push(dom.map_or(0, |dom| depth[dom] + 1));
+            idom.push(dom);
+        }
+        let mut costs: TiVec<InstIdx, f32> = idom.iter().map(|_| 1.0).collect();
+        for (idx, dom) in idom.iter_enumerated().rev() {
+            if let Some(dom) = *dom {
+                costs[dom] += costs[idx];
+            }
+        }
+        Costs { costs, split: false }
+    }
+}
+
+/// How a [`CostKind`] attributes costs, used by
+/// [`InstGraph::extend`](super::inst_graph::InstGraph::extend) to update only
+/// the costs which changed.
+pub(super) enum CostShape {
+    /// Each instantiation costs its weight plus a share of the cost of every
+    /// instantiation which depends on it.
+    Split(fn(&Instantiation) -> f32),
+    /// Each instantiation costs 1 for every instantiation which depends on
+    /// it, directly or indirectly, including itself.
+    Inclusive,
+    /// Each instantiation costs 1 for every instantiation which depends on
+    /// it along every path, including itself.
+    Exclusive,
+}
+
+impl CostKind {
+    pub(super) fn shape(&self) -> CostShape {
+        match self {
+            Self::SplitShare => CostShape::Split(|_| 1.0),
+            Self::InclusiveSubtree => CostShape::Inclusive,
+            Self::ExclusiveSubtree => CostShape::Exclusive,
+            Self::YieldsWeighted => CostShape::Split(|inst| 1.0 + inst.yields_terms.len() as f32),
+            Self::GenerationWeighted => CostShape::Split(|inst| 1.0 + inst.z3_generation.unwrap_or_default() as f32),
+        }
+    }
+}
+
+impl CostModel for CostKind {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SplitShare => "split-share",
+            Self::InclusiveSubtree => "inclusive-subtree",
+            Self::ExclusiveSubtree => "exclusive-subtree",
+            Self::YieldsWeighted => "yields-weighted",
+            Self::GenerationWeighted => "generation-weighted",
+        }
+    }
+    fn costs(&self, parser: &Z3Parser) -> Costs {
+        match self.shape() {
+            CostShape::Split(weight) => Self::split_share(parser, weight),
+            CostShape::Inclusive => Self::inclusive_subtree(parser),
+            CostShape::Exclusive => Self::exclusive_subtree(parser),
+        }
+    }
+}
+
+impl fmt::Display for CostKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The instantiations which created the enodes blamed by `inst`, one for each
+/// blamed enode (so possibly repeated).
+pub(super) fn parents<'a>(parser: &'a Z3Parser, inst: &'a Instantiation) -> impl Iterator<Item = InstIdx> + 'a {
+    parser.insts[inst.match_]
+        .due_to_enodes()
+        .filter_map(|(_, blame)| parser[blame].created_by)
+}
+
+/// The cost of each instantiation under some [`CostModel`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Costs {
+    costs: TiVec<InstIdx, f32>,
+    /// Whether each instantiation passes its cost on to the instantiations
+    /// it depends on, see [`of_quants`](Self::of_quants).
+    split: bool,
+}
+
+impl Costs {
+    pub fn iter(&self) -> impl Iterator<Item = (InstIdx, f32)> + '_ {
+        self.costs.iter_enumerated().map(|(idx, cost)| (idx, *cost))
+    }
+    pub fn len(&self) -> usize {
+        self.costs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+    /// The cost of each quantifier. For the models which split the cost of
+    /// an instantiation among those it depends on (such as the default
+    /// [`CostKind::SplitShare`]) this is the cost its instantiations pass on,
+    /// as before cost models could be chosen; instantiations which depend on
+    /// nothing do not count. For the others it is the summed cost of its
+    /// instantiations.
+    pub fn of_quants(&self, parser: &Z3Parser) -> TiVec<QuantIdx, f32> {
+        let mut quants: TiVec<QuantIdx, f32> = parser.quantifiers.iter().map(|_| 0.0).collect();
+        for (idx, inst) in parser.insts.insts.iter_enumerated().rev() {
+            let Some(quant) = parser.insts[inst.match_].kind.quant_idx() else {
+                continue;
+            };
+            if !self.split {
+                quants[quant] += self.costs[idx];
+                continue;
+            }
+            let deps = parents(parser, inst).count();
+            let share = self.costs[idx] / deps as f32;
+            for _ in 0..deps {
+                quants[quant] += share;
+            }
+        }
+        quants
+    }
+}
+
+impl std::ops::Index<InstIdx> for Costs {
+    type Output = f32;
+    fn index(&self, idx: InstIdx) -> &Self::Output {
+        &self.costs[idx]
+    }
+}
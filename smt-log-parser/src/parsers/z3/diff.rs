@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+
+use fxhash::FxHashMap;
+use serde::Serialize;
+
+use crate::{
+    display_with::{DisplayCtxt, DisplayWithCtxt},
+    items::{QuantIdx, QuantKind},
+};
+
+use super::{inst_graph::InstGraph, z3parser::Z3Parser};
+
+/// A comparison of the quantifier instantiations of two logs, e.g. of the
+/// same proof before and after a change which slowed it down.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LogDiff {
+    /// Quantifiers with instantiations in either log, those with the largest
+    /// change in cost first.
+    pub quantifiers: Vec<QuantDiff>,
+}
+
+/// The instantiations of a quantifier in the old and new log, `None` if the
+/// quantifier is missing from one of them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuantDiff {
+    pub old: Option<QuantSummary>,
+    pub new: Option<QuantSummary>,
+    /// The change from old to new, a missing quantifier counts as having no
+    /// instantiations.
+    pub delta: QuantDelta,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuantSummary {
+    #[serde(skip)]
+    pub quant: QuantIdx,
+    pub name: String,
+    /// The pretty-printed quantifier, `None` for theory solving.
+    pub body: Option<String>,
+    pub insts: usize,
+    /// The summed cost of all instantiations, under the cost model of the
+    /// graph.
+    pub cost: f32,
+    /// See [`QuantChains`](super::inst_graph::QuantChains).
+    pub matching_loops: usize,
+    /// The greatest depth of an instantiation in the graph.
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct QuantDelta {
+    pub insts: isize,
+    pub cost: f32,
+    pub matching_loops: isize,
+    pub max_depth: isize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffStatus {
+    /// Only instantiated in the new log.
+    Added,
+    /// Only instantiated in the old log.
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+impl QuantDiff {
+    fn new(old: Option<QuantSummary>, new: Option<QuantSummary>) -> Self {
+        let field = |summary: &Option<QuantSummary>, f: fn(&QuantSummary) -> usize| summary.as_ref().map_or(0, f) as isize;
+        let delta = QuantDelta {
+            insts: field(&new, |s| s.insts) - field(&old, |s| s.insts),
+            cost: new.as_ref().map_or(0.0, |s| s.cost) - old.as_ref().map_or(0.0, |s| s.cost),
+            matching_loops: field(&new, |s| s.matching_loops) - field(&old, |s| s.matching_loops),
+            max_depth: field(&new, |s| s.max_depth.unwrap_or_default())
+                - field(&old, |s| s.max_depth.unwrap_or_default()),
+        };
+        Self { old, new, delta }
+    }
+
+    /// The name in the new log, or the old one if it was removed.
+    pub fn name(&self) -> &str {
+        let summary = self.new.as_ref().or(self.old.as_ref());
+        &summary.expect("either side is present").name
+    }
+
+    pub fn status(&self) -> DiffStatus {
+        let instantiated = |summary: &Option<QuantSummary>| summary.as_ref().is_some_and(|s| s.insts > 0);
+        match (instantiated(&self.old), instantiated(&self.new)) {
+            (false, true) => DiffStatus::Added,
+            (true, false) => DiffStatus::Removed,
+            _ if self.delta == QuantDelta::default() => DiffStatus::Unchanged,
+            _ => DiffStatus::Changed,
+        }
+    }
+}
+
+impl LogDiff {
+    /// Compare two logs, where each graph was built from the corresponding
+    /// parser. Quantifiers are aligned by name, except unnamed (`name!id`)
+    /// quantifiers whose ids change between runs: these are aligned by the
+    /// name without the id and their pretty-printed body. Any remaining
+    /// quantifiers are then aligned by body alone, e.g. if they were renamed.
+    ///
+    /// Counting matching loops resets the visibility of the graphs' nodes.
+    pub fn new(old: &Z3Parser, old_graph: &mut InstGraph, new: &Z3Parser, new_graph: &mut InstGraph) -> Self {
+        let old = Self::summaries(old, old_graph);
+        let mut new: Vec<_> = Self::summaries(new, new_graph).into_iter().map(Some).collect();
+
+        let mut unmatched_old = Vec::new();
+        let mut quantifiers = Vec::new();
+        let mut by_key = Self::index(&new, |(key, _)| Some(key.clone()));
+        for (key, summary) in old {
+            match by_key.get_mut(&key).and_then(VecDeque::pop_front) {
+                Some(idx) => quantifiers.push(QuantDiff::new(Some(summary), new[idx].take().map(|(_, s)| s))),
+                None => unmatched_old.push(summary),
+            }
+        }
+        let mut by_body = Self::index(&new, |(_, summary)| summary.body.clone());
+        for summary in unmatched_old {
+            let idx = summary.body.as_ref().and_then(|body| by_body.get_mut(body)?.pop_front());
+            let new = idx.and_then(|idx| new[idx].take()).map(|(_, s)| s);
+            quantifiers.push(QuantDiff::new(Some(summary), new));
+        }
+        quantifiers.extend(new.into_iter().flatten().map(|(_, s)| QuantDiff::new(None, Some(s))));
+
+        quantifiers.retain(|diff| diff.old.iter().chain(&diff.new).any(|summary| summary.insts > 0));
+        quantifiers.sort_by(|a, b| b.delta.cost.abs().total_cmp(&a.delta.cost.abs()).then_with(|| a.name().cmp(b.name())));
+        Self { quantifiers }
+    }
+
+    pub fn with_status(&self, status: DiffStatus) -> impl Iterator<Item = &QuantDiff> + '_ {
+        self.quantifiers.iter().filter(move |diff| diff.status() == status)
+    }
+
+    fn summaries(parser: &Z3Parser, graph: &mut InstGraph) -> Vec<(AlignKey, QuantSummary)> {
+        let ctxt = DisplayCtxt {
+            parser,
+            display_term_ids: false,
+            display_quantifier_name: false,
+            use_mathematical_symbols: true,
+        };
+        let stats = parser.quant_stats(&parser.inst_costs(&graph.cost_model()));
+        let chains = graph.quant_chains();
+        let mut max_depths: FxHashMap<QuantIdx, usize> = FxHashMap::default();
+        for node in graph.orig_graph.node_weights() {
+            if let Some(quant) = node.mkind.quant_idx() {
+                let max_depth = max_depths.entry(quant).or_default();
+                *max_depth = (*max_depth).max(node.max_depth());
+            }
+        }
+        stats
+            .into_iter_enumerated()
+            .map(|(quant, stats)| {
+                let body = parser.quantifiers[quant].term.map(|term| term.with(&ctxt).to_string());
+                let key = match &parser.quantifiers[quant].kind {
+                    QuantKind::Other(name) | QuantKind::NamedQuant(name) => {
+                        AlignKey::Name(parser.strings[*name].to_string())
+                    }
+                    QuantKind::UnnamedQuant { name, .. } => {
+                        AlignKey::Body(parser.strings[*name].to_string(), body.clone())
+                    }
+                    QuantKind::Lambda => AlignKey::Body(String::new(), body.clone()),
+                };
+                let summary = QuantSummary {
+                    quant,
+                    name: parser.quant_name(quant),
+                    body,
+                    insts: stats.insts,
+                    cost: stats.cost,
+                    matching_loops: chains.get(&quant).map_or(0, |chains| chains.matching_loops),
+                    max_depth: max_depths.get(&quant).copied(),
+                };
+                (key, summary)
+            })
+            .collect()
+    }
+
+    /// The indices of the quantifiers with each key, in order.
+    fn index<K: std::hash::Hash + Eq>(
+        summaries: &[Option<(AlignKey, QuantSummary)>],
+        key: impl Fn(&(AlignKey, QuantSummary)) -> Option<K>,
+    ) -> FxHashMap<K, VecDeque<usize>> {
+        let mut index: FxHashMap<K, VecDeque<usize>> = FxHashMap::default();
+        for (idx, summary) in summaries.iter().enumerate() {
+            if let Some(key) = summary.as_ref().and_then(&key) {
+                index.entry(key).or_default().push_back(idx);
+            }
+        }
+        index
+    }
+}
+
+/// What quantifiers are aligned by in the first pass of [`LogDiff::new`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum AlignKey {
+    Name(String),
+    /// The name without the id and the body.
+    Body(String, Option<String>),
+}
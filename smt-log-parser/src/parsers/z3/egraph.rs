@@ -1,17 +1,18 @@
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
 use typed_index_collections::TiVec;
 
 use crate::{
     Result,
-    items::{ENodeIdx, EqualityExpl, InstIdx, StackIdx, TermIdx, BlameKind}, Error
+    items::{CheckIdx, ENodeIdx, EqualityDependency, EqualityExpl, InstIdx, MatchIdx, StackIdx, TermIdx, BlameKind}, Error
 };
 
 use super::stack::Stack;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct EGraph {
     term_to_enode: FxHashMap<TermIdx, ENodeIdx>,
-    enodes: TiVec<ENodeIdx, ENode>,
+    pub(super) enodes: TiVec<ENodeIdx, ENode>,
 }
 
 impl EGraph {
@@ -20,6 +21,7 @@ impl EGraph {
         created_by: Option<InstIdx>,
         term: TermIdx,
         z3_generation: Option<u32>,
+        check: Option<CheckIdx>,
         stack: &Stack,
     ) -> Result<ENodeIdx> {
         // TODO: why does this happen sometimes?
@@ -36,6 +38,7 @@ impl EGraph {
             created_by,
             owner: term,
             z3_generation,
+            check,
             equalities: Vec::new(),
         });
         self.term_to_enode.try_reserve(1)?;
@@ -67,7 +70,7 @@ impl EGraph {
         let enode = &mut self.enodes[from];
         let to = expl.to();
         let eq = Equality {
-            _frame: stack.active_frame(),
+            frame: stack.active_frame(),
             to,
             expl,
         };
@@ -109,13 +112,14 @@ impl EGraph {
         path
     }
 
-    pub fn get_equalities<'a: 'b, 'b>(&'a self, from: ENodeIdx, to: ENodeIdx, stack: &'b Stack, can_mismatch: impl Fn() -> bool) -> Result<impl Iterator<Item = &'a EqualityExpl> + 'b> {
+    /// The paths from `from` and `to` to their roots, and the length of the
+    /// part shared by both paths which does not need to be explained.
+    fn paths_to_root(&self, from: ENodeIdx, to: ENodeIdx, stack: &Stack, mut can_mismatch: impl FnMut() -> bool) -> Result<(Vec<ENodeIdx>, Vec<ENodeIdx>, usize)> {
         let f_path = self.path_to_root(from, stack, 0);
         let t_path = self.path_to_root(to, stack, 0);
         let mut shared = 1;
         if f_path[0] != t_path[0] {
-            // Root may not always be the same from v4.12.3 onwards if `to` is an `ite` expression. See:
-            // https://github.com/Z3Prover/z3/commit/faf14012ba18d21c1fcddbdc321ac127f019fa03#diff-0a9ec50ded668e51578edc67ecfe32380336b9cbf12c5d297e2d3759a7a39847R2417-R2419
+            // See `Quirk::IfTermRootMismatch`.
             if !can_mismatch() {
                 return Err(Error::EnodeRootMismatch(from, to));
             }
@@ -125,27 +129,58 @@ impl EGraph {
         while shared < f_path.len() && shared < t_path.len() && f_path[shared] == t_path[shared] {
             shared += 1;
         }
+        Ok((f_path, t_path, shared))
+    }
+
+    pub fn get_equalities<'a: 'b, 'b>(&'a self, from: ENodeIdx, to: ENodeIdx, stack: &'b Stack, can_mismatch: impl FnMut() -> bool) -> Result<impl Iterator<Item = &'a EqualityExpl> + 'b> {
+        let (f_path, t_path, shared) = self.paths_to_root(from, to, stack, can_mismatch)?;
         let all = f_path.into_iter().skip(shared).rev().chain(t_path.into_iter().skip(shared));
         Ok(all.map(|idx| &self.enodes[idx].get_equality(stack).unwrap().expl))
     }
 
-    pub fn blame_equalities(&self, from: ENodeIdx, to: ENodeIdx, stack: &Stack, blamed: &mut Vec<BlameKind>, can_mismatch: impl Fn() -> bool) -> Result<()> {
-        for eq in self.get_equalities(from, to, stack, can_mismatch)? {
-            // TODO: figure out if this is all the blames we need.
-            match eq {
-                EqualityExpl::Root { .. } => unreachable!(),
-                &EqualityExpl::Literal { eq, .. } => {
+    /// Record every enode on `path` whose equality from an active frame
+    /// differs from its latest equality.
+    fn frame_mismatches(&self, path: &[ENodeIdx], stack: &Stack, mismatches: &mut EqualityMismatches) -> Result<()> {
+        for &enode in path {
+            let enode_data = &self.enodes[enode];
+            // Only an equality made in a popped frame can be skipped.
+            let Some(latest) = enode_data.equalities.last().filter(|eq| eq.frame.is_some_and(|f| !stack.stack_frames[f].active)) else {
+                continue;
+            };
+            let active = enode_data.get_equality(stack).map(|eq| eq.to);
+            mismatches.insert(EqualityMismatch { enode, latest: latest.to, active })?;
+        }
+        Ok(())
+    }
+
+    /// Blame the equalities connecting `from` and `to`. Enodes whose
+    /// equality lookup was changed by ignoring popped frames are added to
+    /// `mismatches`.
+    pub fn blame_equalities(&self, from: ENodeIdx, to: ENodeIdx, stack: &Stack, blamed: &mut Vec<BlameKind>, mismatches: &mut EqualityMismatches, can_mismatch: impl FnMut() -> bool) -> Result<()> {
+        let (f_path, t_path, shared) = self.paths_to_root(from, to, stack, can_mismatch)?;
+        self.frame_mismatches(&f_path, stack, mismatches)?;
+        self.frame_mismatches(&t_path, stack, mismatches)?;
+        let all = f_path.into_iter().skip(shared).rev().chain(t_path.into_iter().skip(shared));
+        for idx in all {
+            let eq = &self.enodes[idx].get_equality(stack).unwrap().expl;
+            match eq.dependency_on() {
+                EqualityDependency::None => unreachable!(),
+                EqualityDependency::Literal(eq) => {
+                    blamed.try_reserve(1)?;
                     blamed.push(BlameKind::Equality { eq });
                 }
-                EqualityExpl::Congruence { arg_eqs, .. } => {
+                EqualityDependency::Congruence(arg_eqs) => {
                     for (from, to) in arg_eqs.iter() {
                         fn cannot_mismatch() -> bool { false }
-                        self.blame_equalities(*from, *to, stack, blamed, cannot_mismatch)?;
+                        self.blame_equalities(*from, *to, stack, blamed, mismatches, cannot_mismatch)?;
+                    }
+                }
+                EqualityDependency::Terms { from, to, source } => {
+                    blamed.try_reserve(2)?;
+                    for term in [from, to] {
+                        blamed.push(BlameKind::TheoryEquality { term, from, to, source });
                     }
                 }
-                EqualityExpl::Theory { .. } => (),
-                EqualityExpl::Axiom { .. } => (),
-                EqualityExpl::Unknown { .. } => (),
             }
         }
         Ok(())
@@ -159,27 +194,75 @@ impl std::ops::Index<ENodeIdx> for EGraph {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ENode {
     frame: Option<StackIdx>,
     pub created_by: Option<InstIdx>,
     pub owner: TermIdx,
     pub z3_generation: Option<u32>,
+    /// The check during which this enode was created, `None` for enodes
+    /// created while asserting.
+    pub check: Option<CheckIdx>,
 
     equalities: Vec<Equality>,
 }
 
 impl ENode {
-    pub fn get_equality(&self, _stack: &Stack) -> Option<&Equality> {
-        // TODO: why are we allowed to use equalities from popped stack frames?
-        // self.equalities.iter().rev().find(|eq| eq.frame.map(|f| stack.stack_frames[f].active).unwrap_or(true))
-        self.equalities.last()
+    /// The latest equality of this enode which was not made in a popped
+    /// stack frame.
+    pub fn get_equality(&self, stack: &Stack) -> Option<&Equality> {
+        self.equalities.iter().rev().find(|eq| eq.frame.is_none_or(|f| stack.stack_frames[f].active))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Equality {
-    _frame: Option<StackIdx>,
+    frame: Option<StackIdx>,
     pub to: ENodeIdx,
     pub expl: EqualityExpl,
 }
+
+/// An enode whose equality lookup changed when equalities from popped stack
+/// frames started being ignored. Previously the latest equality was always
+/// used, which could blame the wrong equalities in the instantiation graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EqualityMismatch {
+    pub enode: ENodeIdx,
+    /// What the latest equality (possibly from a popped frame) points to.
+    pub latest: ENodeIdx,
+    /// What the latest equality from an active frame points to, `None` if
+    /// all of its equalities were made in popped frames.
+    pub active: Option<ENodeIdx>,
+}
+
+/// The [`EqualityMismatch`]es found while blaming the equalities of a
+/// single match, in the order they were found. An enode is recorded once per
+/// latest equality, even if it is on the path of several blamed equalities.
+#[derive(Debug, Default)]
+pub struct EqualityMismatches {
+    seen: FxHashSet<(ENodeIdx, ENodeIdx)>,
+    found: Vec<EqualityMismatch>,
+}
+
+impl EqualityMismatches {
+    fn insert(&mut self, mismatch: EqualityMismatch) -> Result<()> {
+        self.seen.try_reserve(1)?;
+        if self.seen.insert((mismatch.enode, mismatch.latest)) {
+            self.found.try_reserve(1)?;
+            self.found.push(mismatch);
+        }
+        Ok(())
+    }
+
+    pub fn into_vec(self) -> Vec<EqualityMismatch> {
+        self.found
+    }
+}
+
+/// An [`EqualityMismatch`] found while parsing the blamed equalities of a
+/// match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchEqualityMismatch {
+    pub match_: MatchIdx,
+    pub mismatch: EqualityMismatch,
+}
@@ -0,0 +1,313 @@
+use std::io::{self, Write};
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::EdgeRef,
+    Graph,
+};
+use serde::Serialize;
+
+use crate::items::BlameKind;
+
+use super::{
+    cost::CostKind,
+    inst_graph::{EdgeType, InstGraph, NodeData},
+    z3parser::Z3Parser,
+};
+
+/// The instantiation graph in a form which can be written to a file for
+/// other tools: GraphML (yEd), GEXF (Gephi) or, through its [`Serialize`]
+/// implementation, JSON of the form
+///
+/// ```json
+/// {
+///   "cost_model": "split-share",
+///   "nodes": [{ "inst": 0, "quantifier": "split", "theory": false, "cost": 4.0,
+///               "min_depth": 0, "max_depth": 0, "generation": 1, "fingerprint": "0x1" }],
+///   "edges": [{ "source": 0, "target": 1, "type": "direct", "blame": "term", "blamed_enode": 6 }]
+/// }
+/// ```
+///
+/// where optional fields are `null` if unknown.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportedGraph {
+    /// The cost model under which the node costs were computed.
+    pub cost_model: CostKind,
+    pub nodes: Vec<ExportedNode>,
+    pub edges: Vec<ExportedEdge>,
+}
+
+/// An instantiation, identified by its index in the log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportedNode {
+    pub inst: usize,
+    /// `None` for theory solving.
+    pub quantifier: Option<String>,
+    /// Whether the instantiation was discovered by a theory solver rather
+    /// than an E-matching match.
+    pub theory: bool,
+    pub cost: f32,
+    /// The length of the shortest and longest path from a root.
+    pub min_depth: Option<usize>,
+    pub max_depth: usize,
+    /// The Z3 generation, if logged.
+    pub generation: Option<u32>,
+    /// In hexadecimal, as in the log.
+    pub fingerprint: String,
+}
+
+/// A dependency of the `target` instantiation on the `source` one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportedEdge {
+    pub source: usize,
+    pub target: usize,
+    #[serde(rename = "type")]
+    pub kind: ExportedEdgeKind,
+    /// How the `target` match blamed the `source` instantiation, `None` for
+    /// indirect edges.
+    pub blame: Option<ExportedBlame>,
+    /// The e-node created by `source` which `target` matched on.
+    pub blamed_enode: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportedEdgeKind {
+    Direct,
+    /// A path through instantiations which are hidden in the visible graph.
+    Indirect,
+}
+
+/// The kind of a [`BlameKind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportedBlame {
+    Term,
+    Equality,
+    TheoryEquality,
+}
+
+impl From<&BlameKind> for ExportedBlame {
+    fn from(kind: &BlameKind) -> Self {
+        match kind {
+            BlameKind::Term { .. } => Self::Term,
+            BlameKind::Equality { .. } => Self::Equality,
+            BlameKind::TheoryEquality { .. } => Self::TheoryEquality,
+        }
+    }
+}
+
+impl ExportedGraph {
+    /// All instantiations of the log, regardless of their visibility.
+    pub fn orig(parser: &Z3Parser, graph: &InstGraph) -> Self {
+        Self::new(parser, graph, &graph.orig_graph, |blame| (ExportedEdgeKind::Direct, Some(blame)))
+    }
+
+    /// Only the visible instantiations, see
+    /// [`InstGraph::retain_visible_nodes_and_reconnect`].
+    pub fn visible(parser: &Z3Parser, graph: &InstGraph) -> Self {
+        Self::new(parser, graph, &graph.visible_graph, |edge| match edge {
+            EdgeType::Direct { kind, .. } => (ExportedEdgeKind::Direct, Some(kind)),
+            EdgeType::Indirect => (ExportedEdgeKind::Indirect, None),
+        })
+    }
+
+    fn new<E>(
+        parser: &Z3Parser,
+        graph: &InstGraph,
+        exported: &Graph<NodeData, E>,
+        edge_kind: impl Fn(&E) -> (ExportedEdgeKind, Option<&BlameKind>),
+    ) -> Self {
+        let nodes = exported
+            .node_weights()
+            .map(|node| {
+                let inst = &parser.insts[node.inst_idx];
+                ExportedNode {
+                    inst: node.inst_idx.into(),
+                    quantifier: node.mkind.quant_idx().map(|quant| parser.quant_name(quant)),
+                    theory: node.is_theory_inst,
+                    cost: node.cost(),
+                    min_depth: node.min_depth,
+                    max_depth: node.max_depth(),
+                    generation: inst.z3_generation,
+                    fingerprint: format!("{:#x}", inst.fingerprint.0),
+                }
+            })
+            .collect();
+        let inst = |idx: NodeIndex| usize::from(exported[idx].inst_idx);
+        let edges = exported
+            .edge_references()
+            .map(|edge| {
+                let (kind, blame) = edge_kind(edge.weight());
+                ExportedEdge {
+                    source: inst(edge.source()),
+                    target: inst(edge.target()),
+                    kind,
+                    blame: blame.map(ExportedBlame::from),
+                    blamed_enode: blame.and_then(BlameKind::get_blame_node).map(usize::from),
+                }
+            })
+            .collect();
+        Self { cost_model: graph.cost_model(), nodes, edges }
+    }
+
+    /// Write the graph as GraphML, with the fields of [`ExportedNode`] and
+    /// [`ExportedEdge`] as `<data>` (omitted where `None`).
+    pub fn write_graphml(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(out, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+        for (class, name, ty) in attr_decls() {
+            writeln!(out, r#"  <key id="{name}" for="{class}" attr.name="{name}" attr.type="{}"/>"#, ty.name())?;
+        }
+        writeln!(out, r#"  <graph id="G" edgedefault="directed">"#)?;
+        for node in &self.nodes {
+            writeln!(out, r#"    <node id="i{}">"#, node.inst)?;
+            for (name, value) in NODE_ATTRS.iter().filter_map(|attr| attr.value(node)) {
+                writeln!(out, r#"      <data key="{name}">{}</data>"#, escape(&value))?;
+            }
+            writeln!(out, "    </node>")?;
+        }
+        for (idx, edge) in self.edges.iter().enumerate() {
+            writeln!(out, r#"    <edge id="e{idx}" source="i{}" target="i{}">"#, edge.source, edge.target)?;
+            for (name, value) in EDGE_ATTRS.iter().filter_map(|attr| attr.value(edge)) {
+                writeln!(out, r#"      <data key="{name}">{}</data>"#, escape(&value))?;
+            }
+            writeln!(out, "    </edge>")?;
+        }
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</graphml>")
+    }
+
+    /// Write the graph as GEXF 1.3, with the fields of [`ExportedNode`] and
+    /// [`ExportedEdge`] as `<attvalue>`s (omitted where `None`). Nodes are
+    /// labelled with their quantifier.
+    pub fn write_gexf(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+        writeln!(out, r#"  <graph defaultedgetype="directed" mode="static">"#)?;
+        for class in ["node", "edge"] {
+            writeln!(out, r#"    <attributes class="{class}">"#)?;
+            for (_, name, ty) in attr_decls().filter(|(of, ..)| *of == class) {
+                writeln!(out, r#"      <attribute id="{name}" title="{name}" type="{}"/>"#, ty.name())?;
+            }
+            writeln!(out, "    </attributes>")?;
+        }
+        writeln!(out, "    <nodes>")?;
+        for node in &self.nodes {
+            let label = node.quantifier.as_deref().unwrap_or("theory-solving");
+            writeln!(out, r#"      <node id="i{}" label="{}">"#, node.inst, escape(label))?;
+            write_attvalues(out, NODE_ATTRS.iter().filter_map(|attr| attr.value(node)))?;
+            writeln!(out, "      </node>")?;
+        }
+        writeln!(out, "    </nodes>")?;
+        writeln!(out, "    <edges>")?;
+        for (idx, edge) in self.edges.iter().enumerate() {
+            writeln!(out, r#"      <edge id="e{idx}" source="i{}" target="i{}">"#, edge.source, edge.target)?;
+            write_attvalues(out, EDGE_ATTRS.iter().filter_map(|attr| attr.value(edge)))?;
+            writeln!(out, "      </edge>")?;
+        }
+        writeln!(out, "    </edges>")?;
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</gexf>")
+    }
+}
+
+fn write_attvalues(out: &mut impl Write, values: impl Iterator<Item = (&'static str, String)>) -> io::Result<()> {
+    writeln!(out, "        <attvalues>")?;
+    for (name, value) in values {
+        writeln!(out, r#"          <attvalue for="{name}" value="{}"/>"#, escape(&value))?;
+    }
+    writeln!(out, "        </attvalues>")
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[derive(Clone, Copy)]
+enum AttrType {
+    String,
+    Boolean,
+    Long,
+    Double,
+}
+
+impl AttrType {
+    /// The name of the type, which GraphML and GEXF share.
+    fn name(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Boolean => "boolean",
+            Self::Long => "long",
+            Self::Double => "double",
+        }
+    }
+}
+
+/// A field written as an attribute in the XML formats.
+struct Attr<T> {
+    name: &'static str,
+    ty: AttrType,
+    value: fn(&T) -> Option<String>,
+}
+
+impl<T> Attr<T> {
+    fn value(&self, item: &T) -> Option<(&'static str, String)> {
+        (self.value)(item).map(|value| (self.name, value))
+    }
+}
+
+/// The class (`node` or `edge`), name and type of every attribute.
+fn attr_decls() -> impl Iterator<Item = (&'static str, &'static str, AttrType)> {
+    let nodes = NODE_ATTRS.iter().map(|attr| ("node", attr.name, attr.ty));
+    nodes.chain(EDGE_ATTRS.iter().map(|attr| ("edge", attr.name, attr.ty)))
+}
+
+const NODE_ATTRS: &[Attr<ExportedNode>] = &[
+    Attr { name: "inst", ty: AttrType::Long, value: |node| Some(node.inst.to_string()) },
+    Attr { name: "quantifier", ty: AttrType::String, value: |node| node.quantifier.clone() },
+    Attr { name: "theory", ty: AttrType::Boolean, value: |node| Some(node.theory.to_string()) },
+    Attr { name: "cost", ty: AttrType::Double, value: |node| Some(node.cost.to_string()) },
+    Attr { name: "min_depth", ty: AttrType::Long, value: |node| node.min_depth.map(|d| d.to_string()) },
+    Attr { name: "max_depth", ty: AttrType::Long, value: |node| Some(node.max_depth.to_string()) },
+    Attr { name: "generation", ty: AttrType::Long, value: |node| node.generation.map(|g| g.to_string()) },
+    Attr { name: "fingerprint", ty: AttrType::String, value: |node| Some(node.fingerprint.clone()) },
+];
+
+const EDGE_ATTRS: &[Attr<ExportedEdge>] = &[
+    Attr { name: "type", ty: AttrType::String, value: |edge| Some(edge.kind.name().to_string()) },
+    Attr { name: "blame", ty: AttrType::String, value: |edge| edge.blame.map(|blame| blame.name().to_string()) },
+    Attr { name: "blamed_enode", ty: AttrType::Long, value: |edge| edge.blamed_enode.map(|e| e.to_string()) },
+];
+
+impl ExportedEdgeKind {
+    /// The name used in all formats.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Direct => "direct",
+            Self::Indirect => "indirect",
+        }
+    }
+}
+
+impl ExportedBlame {
+    /// The name used in all formats.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Term => "term",
+            Self::Equality => "equality",
+            Self::TheoryEquality => "theory_equality",
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    items::{
+        BlameKind, Check, CheckResult, ENodeIdx, Fingerprint, InstIdx, Instantiation, Match, MatchKind, QuantIdx,
+        QuantKind, Quantifier, Term, TermIdx, TermKind, VarNames,
+    },
+    Error, Result,
+};
+
+use super::z3parser::Z3Parser;
+
+/// Building the parser state from the logs of other solvers (see
+/// [`Cvc5Parser`](crate::parsers::cvc5::cvc5parser::Cvc5Parser)). Terms
+/// created this way have no id and are shared between all their uses.
+impl Z3Parser {
+    pub(crate) fn import_term(&mut self, kind: TermKind, children: Vec<TermIdx>) -> TermIdx {
+        self.terms.new_synthetic_term(kind, children, None)
+    }
+
+    /// Add a new quantifier, `children` are the patterns followed by the body.
+    /// Quantifiers which are only known by name have no term.
+    pub(crate) fn import_quant(
+        &mut self,
+        kind: QuantKind,
+        vars: Option<VarNames>,
+        num_vars: usize,
+        children: Option<Vec<TermIdx>>,
+    ) -> Result<QuantIdx> {
+        let qidx = self.quantifiers.next_key();
+        let term = children.map(|children| {
+            let term = Term { id: None, kind: TermKind::Quant(qidx), child_ids: children.into_boxed_slice() };
+            self.terms.new_term(term)
+        });
+        let q = Quantifier { kind, num_vars, term: term.transpose()?, instances: Vec::new(), vars };
+        self.quantifiers.raw.try_reserve(1)?;
+        Ok(self.quantifiers.push_and_get_key(q))
+    }
+
+    /// Start a new check which has already finished with `result`.
+    pub(crate) fn import_check(&mut self, result: CheckResult) -> Result<()> {
+        self.checks.raw.try_reserve(1)?;
+        let check = self.checks.push_and_get_key(Check { scope: 0, result: Some(result) });
+        self.active_check = Some(check);
+        Ok(())
+    }
+
+    /// The enode of `term`, which is created as a ground term of the input if
+    /// it does not exist yet.
+    pub(crate) fn import_enode(&mut self, term: TermIdx) -> Result<ENodeIdx> {
+        match self.egraph.get_enode(term, &self.stack) {
+            Err(Error::UnknownEnode(_)) => {
+                self.egraph.new_enode(None, term, None, self.active_check, &self.stack)
+            }
+            enode => enode,
+        }
+    }
+
+    /// Add an instantiation which resulted in `result` (if known). The terms
+    /// of `yields` which do not have an enode yet are attributed to it.
+    pub(crate) fn import_inst(
+        &mut self,
+        kind: MatchKind,
+        blamed: Vec<BlameKind>,
+        result: Option<TermIdx>,
+        yields: impl IntoIterator<Item = TermIdx>,
+    ) -> Result<InstIdx> {
+        let fingerprint = Fingerprint(self.insts.matches.len() as u64);
+        let match_ = Match { kind, blamed: blamed.into_boxed_slice(), check: self.active_check };
+        let match_ = self.insts.new_match(fingerprint, match_)?;
+        let inst = Instantiation {
+            match_,
+            fingerprint,
+            proof_id: result.map(Ok),
+            z3_generation: None,
+            yields_terms: Default::default(),
+            check: self.active_check,
+            case_split: None,
+        };
+        let iidx = self.insts.new_inst(fingerprint, inst)?;
+        if let Some(quant) = self.insts[match_].kind.quant_idx() {
+            self.quantifiers[quant].instances.try_reserve(1)?;
+            self.quantifiers[quant].instances.push(iidx);
+        }
+        let mut yields_terms = Vec::new();
+        for term in yields {
+            if let Err(Error::UnknownEnode(_)) = self.egraph.get_enode(term, &self.stack) {
+                let enode = self.egraph.new_enode(Some(iidx), term, None, self.active_check, &self.stack)?;
+                yields_terms.try_reserve(1)?;
+                yields_terms.push(enode);
+            }
+        }
+        self.insts[iidx].yields_terms = yields_terms.into_boxed_slice();
+        Ok(iidx)
+    }
+}
@@ -1,13 +1,17 @@
 use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use typed_index_collections::TiVec;
 
 use crate::{items::{Fingerprint, InstIdx, Instantiation, Match, MatchIdx}, Result};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Insts {
     // `theory-solving` fingerprints are always 0, others rarely repeat.
-    fingerprint_to_match: FxHashMap<Fingerprint, (MatchIdx, Option<InstIdx>)>,
+    fingerprint_to_match: FxHashMap<Fingerprint, MatchIdx>,
     pub(super) matches: TiVec<MatchIdx, Match>,
+    /// The instantiation of each match, `None` for matches which were never
+    /// instantiated.
+    match_to_inst: TiVec<MatchIdx, Option<InstIdx>>,
     pub(super) insts: TiVec<InstIdx, Instantiation>,
 
     has_theory_solving_inst: bool,
@@ -18,28 +22,37 @@ impl Insts {
         self.has_theory_solving_inst |= match_.kind.quant_idx().is_none();
         
         self.matches.raw.try_reserve(1)?;
+        self.match_to_inst.raw.try_reserve(1)?;
         let idx = self.matches.push_and_get_key(match_);
-        // Can remove a duplicate fingerprint if that one was never instantiated.
+        self.match_to_inst.push(None);
+        // A repeated fingerprint refers to the newest match from now on, the
+        // older match is kept (whether it was instantiated or not).
         self.fingerprint_to_match.try_reserve(1)?;
-        self.fingerprint_to_match.insert(fingerprint, (idx, None));
+        self.fingerprint_to_match.insert(fingerprint, idx);
         Ok(idx)
     }
 
     pub fn get_match(&self, fingerprint: Fingerprint) -> Option<MatchIdx> {
-        self.fingerprint_to_match.get(&fingerprint).map(|(idx, _)| *idx)
+        self.fingerprint_to_match.get(&fingerprint).copied()
     }
     pub fn new_inst(&mut self, fingerprint: Fingerprint, inst: Instantiation) -> Result<InstIdx> {
-        let (_, inst_idx) = self
-            .fingerprint_to_match
-            .get_mut(&fingerprint)
-            .expect(&format!("{:x}", fingerprint.0));
+        let match_ = self.get_match(fingerprint).expect(&format!("{:x}", fingerprint.0));
         self.insts.raw.try_reserve(1)?;
         let idx = self.insts.push_and_get_key(inst);
-        debug_assert!(inst_idx.is_none(), "duplicate fingerprint");
-        *inst_idx = Some(idx);
+        debug_assert!(self.match_to_inst[match_].is_none(), "duplicate fingerprint");
+        self.match_to_inst[match_] = Some(idx);
         Ok(idx)
     }
 
+    /// The instantiation of `match_`, if it was instantiated.
+    pub fn match_inst(&self, match_: MatchIdx) -> Option<InstIdx> {
+        self.match_to_inst[match_]
+    }
+    /// The matches which were never instantiated.
+    pub fn unused_matches(&self) -> impl Iterator<Item = MatchIdx> + '_ {
+        self.match_to_inst.iter_enumerated().filter(|(_, inst)| inst.is_none()).map(|(idx, _)| idx)
+    }
+
     pub fn has_theory_solving_inst(&self) -> bool {
         self.has_theory_solving_inst
     }
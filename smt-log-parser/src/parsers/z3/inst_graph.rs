@@ -1,8 +1,15 @@
 use fxhash::{FxHashSet, FxHashMap};
+#[cfg(target_arch = "wasm32")]
 use gloo_console::log;
+// The browser console is not available when running natively (e.g. when
+// analysing a partially parsed log from a binary or test).
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ($($t:tt)*) => {};
+}
 use petgraph::graph::NodeIndex;
 use petgraph::stable_graph::StableGraph;
-use petgraph::visit::{Bfs, IntoEdgeReferences, Topo, IntoEdges};
+use petgraph::visit::{IntoEdgeReferences, Topo, IntoEdges};
 use petgraph::{
     stable_graph::EdgeIndex,
     visit::{Dfs, EdgeRef},
@@ -10,20 +17,21 @@ use petgraph::{
 };
 use petgraph::{Direction, Graph};
 use roaring::bitmap::RoaringBitmap;
-use std::cmp::Ordering;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::iter::zip;
 use typed_index_collections::TiVec;
 
 use crate::display_with::{DisplayCtxt, DisplayWithCtxt};
-use crate::items::{BlameKind, ENodeIdx, Fingerprint, InstIdx, MatchKind, Term, TermIdx, QuantIdx, TermKind};
+use crate::items::{BlameKind, CheckIdx, ENodeIdx, Fingerprint, InstIdx, MatchKind, Term, TermIdx, QuantIdx, TermKind};
 
+use super::cost::{CostKind, CostShape};
 use super::terms::Terms;
 use super::z3parser::Z3Parser;
 
 const MIN_MATCHING_LOOP_LENGTH: usize = 3;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NodeData {
     // pub line_nr: usize,
     pub is_theory_inst: bool,
@@ -37,14 +45,38 @@ pub struct NodeData {
     branching_rank: usize,
     pub min_depth: Option<usize>,
     max_depth: usize,
-    topo_ord: usize,
     quantifier: Option<String>,
+    pub check: Option<CheckIdx>,
+    /// Whether the instantiation was used by the final proof, `None` if the
+    /// log contains no proof.
+    pub useful: Option<bool>,
 }
 
 impl NodeData {
     pub fn visible(&self) -> bool {
         self.visible
     }
+    /// The cost under the [cost model](InstGraph::cost_model) of the graph.
+    pub fn cost(&self) -> f32 {
+        self.cost
+    }
+    /// The length of the longest path from a root to this node.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+    /// The position when ordering all nodes by decreasing cost.
+    pub fn cost_rank(&self) -> usize {
+        self.cost_rank
+    }
+    /// The number of instantiations which directly depend on this one.
+    pub fn child_count(&self) -> usize {
+        self.child_count
+    }
+    /// The position when ordering all nodes by decreasing
+    /// [`child_count`](Self::child_count).
+    pub fn branching_rank(&self) -> usize {
+        self.branching_rank
+    }
 }
 
 impl fmt::Debug for NodeData {
@@ -59,7 +91,7 @@ impl From<InstIdx> for NodeIndex {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum EdgeType {
     Direct {
         kind: BlameKind,
@@ -127,7 +159,9 @@ pub struct InstInfo {
     pub inst_idx: InstIdx,
     pub resulting_term: Option<String>,
     pub z3_gen: Option<u32>,
-    pub cost: f32,
+    /// `None` if the cost could not be looked up, see
+    /// [`NodeInfoMap::get_instantiation_info`].
+    pub cost: Option<f32>,
     pub mkind: MatchKind,
     pub quant_discovered: bool,
     pub formula: String,
@@ -137,6 +171,12 @@ pub struct InstInfo {
     pub blamed_terms: Vec<String>,
     pub equality_expls: Vec<String>,
     pub dep_instantiations: Vec<InstIdx>,
+    /// The term originally rewritten by the chain of theory-solving rewrites
+    /// this instantiation is part of.
+    pub rewrite_of: Option<String>,
+    pub rewrite_chain: Vec<InstIdx>,
+    pub rewrite_results: Vec<String>,
+    pub rewrite_consumers: Vec<InstIdx>,
 }
 
 impl PartialEq for InstInfo {
@@ -145,16 +185,22 @@ impl PartialEq for InstInfo {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct InstGraph {
     pub orig_graph: Graph<NodeData, BlameKind>,
     pub visible_graph: Graph<NodeData, EdgeType>,
     cost_ranked_node_indices: Vec<InstIdx>,
     branching_ranked_node_indices: Vec<InstIdx>,
-    tr_closure: Vec<RoaringBitmap>,
+    /// The instantiations from which each one can be reached (including
+    /// itself), i.e. the transitive closure of the graph.
+    ancestors: Vec<RoaringBitmap>,
     matching_loop_subgraph: Graph<NodeData, EdgeType>,
     matching_loop_end_nodes: Option<Vec<NodeIndex>>, // these are sorted by maximal depth in descending order 
     generalized_terms: TiVec<usize, Option<Vec<String>>>,
+    cost_model: CostKind,
+    /// The instantiations which were still being parsed when the graph was
+    /// last extended.
+    open_insts: Vec<InstIdx>,
 }
 
 enum InstOrder {
@@ -162,6 +208,18 @@ enum InstOrder {
     Cost,
 }
 
+/// The chains of instantiations of a single quantifier, see
+/// [`InstGraph::quant_chains`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuantChains {
+    /// The number of instantiations in the longest chain.
+    pub longest: usize,
+    /// The number of chains which end in an instantiation without dependents
+    /// and are long enough to be a potential matching loop, as found by
+    /// [`InstGraph::search_matching_loops`].
+    pub matching_loops: usize,
+}
+
 pub struct VisibleGraphInfo {
     pub node_count: usize,
     pub edge_count: usize,
@@ -196,7 +254,7 @@ impl Terms {
 impl InstGraph {
     pub fn from(parser: &Z3Parser) -> Self {
         let mut inst_graph = Self::default();
-        inst_graph.compute_instantiation_graph(parser);
+        inst_graph.extend(parser);
         inst_graph
     }
 
@@ -297,9 +355,7 @@ impl InstGraph {
     }
 
     fn tr_closure_contains_edge(&self, from: InstIdx, to: InstIdx) -> bool {
-        let topo_ord_from = self.orig_graph.node_weight(from.into()).unwrap().topo_ord;
-        let from_bitset = &self.tr_closure[topo_ord_from];
-        from_bitset.contains(usize::from(to) as u32)
+        self.ancestors[usize::from(to)].contains(usize::from(from) as u32)
     }
 
     pub fn keep_n_most_costly(&mut self, n: usize) {
@@ -464,13 +520,15 @@ impl InstGraph {
     //     }
     // }
 
-    pub fn search_matching_loops(&mut self) -> usize {
+    /// Call `f` with the graph of the instantiations of each quantifier in
+    /// turn, with edges for indirect dependencies through instantiations of
+    /// other quantifiers. Leaves every node visible.
+    fn for_each_quant_subgraph(&mut self, mut f: impl FnMut(QuantIdx, &mut Graph<NodeData, EdgeType>)) {
         let quants: FxHashSet<_> = self
             .orig_graph
             .node_weights()
             .flat_map(|node| node.mkind.quant_idx())
             .collect();
-        let mut matching_loop_nodes_per_quant: Vec<FxHashSet<InstIdx>> = Vec::new();
         log!(format!("Start processing quants"));
         for quant in quants {
             log!(format!("Processing quant {}", quant));
@@ -482,10 +540,40 @@ impl InstGraph {
                     .unwrap_or_default()
             });
             self.retain_visible_nodes_and_reconnect();
-            let matching_loops = Self::find_longest_paths(&mut self.visible_graph);
-            matching_loop_nodes_per_quant.push(matching_loops);
+            f(quant, &mut self.visible_graph);
         }
         log!(format!("Done processing quants"));
+        self.reset_visibility_to(true);
+    }
+
+    /// The chains of instantiations of each quantifier, where each depends
+    /// (possibly indirectly) on the previous one. Long chains indicate a
+    /// matching loop. Resets the visibility of all nodes.
+    pub fn quant_chains(&mut self) -> FxHashMap<QuantIdx, QuantChains> {
+        let mut quant_chains = FxHashMap::default();
+        self.for_each_quant_subgraph(|quant, graph| {
+            Self::compute_longest_distances_from_roots(graph);
+            let end_depths = graph
+                .node_indices()
+                .filter(|nx| graph.neighbors_directed(*nx, Outgoing).count() == 0)
+                .map(|nx| graph[nx].max_depth);
+            let mut chains = QuantChains::default();
+            for depth in end_depths {
+                chains.longest = chains.longest.max(depth + 1);
+                chains.matching_loops += (depth + 1 >= MIN_MATCHING_LOOP_LENGTH) as usize;
+            }
+            quant_chains.insert(quant, chains);
+        });
+        self.retain_visible_nodes_and_reconnect();
+        quant_chains
+    }
+
+    pub fn search_matching_loops(&mut self) -> usize {
+        let mut matching_loop_nodes_per_quant: Vec<FxHashSet<InstIdx>> = Vec::new();
+        self.for_each_quant_subgraph(|_, graph| {
+            let matching_loops = Self::find_longest_paths(graph);
+            matching_loop_nodes_per_quant.push(matching_loops);
+        });
         self.reset_visibility_to(false);
         for matching_loop in matching_loop_nodes_per_quant {
             for node in matching_loop {
@@ -615,6 +703,17 @@ impl InstGraph {
         }
     }
 
+    /// Hide all instantiations which did not happen during the given check.
+    pub fn retain_check(&mut self, check: CheckIdx) {
+        self.retain_nodes(|node| node.check == Some(check))
+    }
+
+    /// Hide all instantiations which were not used by the final proof. Does
+    /// nothing if the log contains no proof.
+    pub fn retain_useful(&mut self) {
+        self.retain_nodes(|node| node.useful != Some(false))
+    }
+
     pub fn show_matching_loop_subgraph(&mut self) {
         self.reset_visibility_to(false);
         for node in self.matching_loop_subgraph.node_weights() {
@@ -710,140 +809,215 @@ impl InstGraph {
         nr_visible_neighbours < nr_hidden_neighbours + nr_visible_neighbours
     }
 
-    fn compute_instantiation_graph(&mut self, parser: &Z3Parser) {
-        for (inst_idx, inst) in parser.insts.insts.iter_enumerated() {
+    /// Extends the graph with the instantiations parsed since it was created
+    /// or last extended, e.g. to show a log while it is still being parsed
+    /// (see [`process_for`](crate::parsers::StreamParser::process_for)). The
+    /// result is the same as building the graph from scratch with
+    /// [`from`](Self::from), except that the visibility of existing nodes and
+    /// any found matching loops are kept. New nodes are visible.
+    ///
+    /// Since instantiations only depend on earlier ones, the depths and
+    /// reachability of existing nodes never change. Costs and child counts
+    /// only change for the ancestors of new nodes, so only these are updated
+    /// and moved in the rankings. Whether an instantiation was useful is only
+    /// known once the proof has been parsed, when it is set for all nodes.
+    pub fn extend(&mut self, parser: &Z3Parser) {
+        let prev_node_count = self.orig_graph.node_count();
+        let mut new_edges = Vec::new();
+        for (inst_idx, inst) in parser.insts.insts.iter_enumerated().skip(prev_node_count) {
             let match_ = &parser.insts[inst.match_];
-            // add new node to graph
+            let parents: Vec<_> = match_
+                .due_to_enodes()
+                .filter_map(|(kind, e)| parser[e].created_by.map(|c| (kind, c)))
+                .collect();
+            // the depths of parents are final since nodes only get children
+            let parent_depths = parents.iter().map(|(_, p)| &self.orig_graph[NodeIndex::from(*p)]);
+            let min_depth = parent_depths.clone().filter_map(|p| p.min_depth).min().map_or(0, |d| d + 1);
+            let max_depth = parent_depths.map(|p| p.max_depth).max().map_or(0, |d| d + 1);
+            // add new node to graph, costs and ranks are set below
             self.add_node(NodeData {
                 is_theory_inst: match_.kind.is_discovered(),
-                cost: inst.cost,
+                cost: 0.0,
                 inst_idx,
                 mkind: match_.kind.clone(),
                 visible: true,
                 child_count: 0,
-                parent_count: 0,
+                parent_count: parents.len(),
                 cost_rank: 0,
                 branching_rank: 0,
-                min_depth: None,
-                max_depth: 0,
-                topo_ord: 0,
+                min_depth: Some(min_depth),
+                max_depth,
                 quantifier: match_.kind.quant_idx().and_then(|q| parser[q].kind.name()).map(|s| parser.strings[s].into()),
+                check: inst.check,
+                useful: None,
             });
-            // then add all edges to previous nodes
-            for (kind, from) in match_
-                .due_to_enodes()
-                .filter_map(|(kind, e)| parser[e].created_by.map(|c| (kind, c)))
-            {
-                self.add_edge(from, inst_idx, kind);
+            // then add all edges to previous nodes, the new node can be
+            // reached from everything which reaches one of its parents
+            let mut ancestors = RoaringBitmap::new();
+            ancestors.insert(usize::from(inst_idx) as u32);
+            for (kind, from) in parents {
+                ancestors |= &self.ancestors[usize::from(from)];
+                self.orig_graph[NodeIndex::from(from)].child_count += 1;
+                new_edges.push(self.add_edge(from, inst_idx, kind));
             }
+            self.ancestors.push(ancestors);
         }
-        // precompute number of children and parents of each node
-        for idx in self.orig_graph.node_indices() {
-            let child_count = self.orig_graph.neighbors_directed(idx, Outgoing).count();
-            let parent_count = self.orig_graph.neighbors_directed(idx, Incoming).count();
-            self.orig_graph.node_weight_mut(idx).unwrap().child_count = child_count;
-            self.orig_graph.node_weight_mut(idx).unwrap().parent_count = parent_count;
-        }
-        // precompute the cost-rank of all nodes by sorting the node_indices by our cost-order
-        // in descending order and then assigning the rank to each node
-        // Our cost-order is defined as follows:
-        // inst_b > inst_a iff (cost_b > cost_a or (cost_b = cost_a and line_nr_b < line_nr_a))
-        // This is a total order since the line numbers are always guaranteed to be distinct
-        // integers.
-        let mut cost_ranked_node_indices: Vec<_> = self.orig_graph.node_weights_mut().collect();
-        let cost_order = |node_a: &&mut NodeData, node_b: &&mut NodeData| {
-            node_a.cost.partial_cmp(&node_b.cost).map(Ordering::reverse).unwrap_or(Ordering::Equal)
-        };
-        cost_ranked_node_indices.sort_by(cost_order);
-        self.cost_ranked_node_indices = cost_ranked_node_indices.iter().map(|n| n.inst_idx).collect();
-        for (i, node) in cost_ranked_node_indices.into_iter().enumerate() {
-            node.cost_rank = i;
+
+        // The instantiations whose cost or child count can change: the new
+        // ones, those which were still being parsed last time (and may have
+        // yielded more terms since) and everything they depend on.
+        let mut changed = RoaringBitmap::new();
+        let open = self.open_insts.iter().map(|&idx| usize::from(idx));
+        for idx in open.chain(prev_node_count..self.orig_graph.node_count()) {
+            changed |= &self.ancestors[idx];
         }
-        // precompute BFS depth such that we can filter the graph up to some specified depth
-        let roots: Vec<NodeIndex> = self
-            .orig_graph
-            .node_indices()
-            .filter(|nx| self.orig_graph.node_weight(*nx).unwrap().parent_count == 0)
-            .collect();
-        for root in roots {
-            let mut bfs = Bfs::new(&self.orig_graph, root);
-            while let Some(nx) = bfs.next(&self.orig_graph) {
-                let parents = self.orig_graph.neighbors_directed(nx, Incoming);
-                let min_parent_depth = parents
-                    .filter_map(|parent| self.orig_graph.node_weight(parent).unwrap().min_depth)
-                    .min();
-                if let Some(depth) = min_parent_depth {
-                    self.orig_graph[nx].min_depth = Some(depth + 1);
-                } else {
-                    // the min_depth is None iff the node at nx has no parents, hence we set the depth to 0
-                    self.orig_graph[nx].min_depth = Some(0);
-                }
+        self.open_insts = parser.inst_stack.iter().map(|(idx, _)| *idx).collect();
+        self.update_costs(parser, prev_node_count, &changed);
+        if let Some(usefulness) = parser.inst_usefulness() {
+            for node in self.orig_graph.node_weights_mut() {
+                node.useful = Some(usefulness[node.inst_idx]);
             }
         }
-        // precompute the branching-rank of all nodes by sorting the node_indices by our branching-order
-        // in descending order and then assigning the rank to each node
-        // Our branching-order is defined as follows:
-        // inst_b > inst_a iff (child_count(b) > child_count(a) or (child_count(b) = child_count(a) and line_nr_b < line_nr_a))
-        // This is a total order since the line numbers are always guaranteed to be distinct
-        // integers.
-        let mut branching_ranked_node_indices: Vec<_> = self.orig_graph.node_weights_mut().collect();
-        let branching_order = |node_a: &&mut NodeData, node_b: &&mut NodeData| {
-            node_a.child_count.cmp(&node_b.child_count).reverse()
-        };
-        branching_ranked_node_indices.sort_by(branching_order);
-        self.branching_ranked_node_indices = branching_ranked_node_indices.iter().map(|n| n.inst_idx).collect();
-        for (i, node) in branching_ranked_node_indices.into_iter().enumerate() {
-            node.branching_rank = i;
-        }
-        // compute the longest distances from root nodes by traversing the graph in topological order
-        // and taking max distance among parents + 1. Needed to compute longest paths through selected
-        // nodes
-        // root nodes (i.e., nodes without parents) have distance 0
-        let mut topo = Topo::new(&self.orig_graph);
-        while let Some(node) = topo.next(&self.orig_graph) {
-            let max_parent_depth = self
-                .orig_graph
-                .neighbors_directed(node, Incoming)
-                .map(|nx| self.orig_graph.node_weight(nx).unwrap().max_depth)
-                .max();
-            if let Some(depth) = max_parent_depth {
-                let node_weight = self.orig_graph.node_weight_mut(node).unwrap();
-                node_weight.max_depth = depth + 1;
+        self.rank_nodes(&changed);
+        self.extend_visible_graph(prev_node_count, new_edges);
+    }
+
+    /// Updates the costs of the `changed` nodes after the nodes from
+    /// `prev_node_count` on were added, see [`extend`](Self::extend). The
+    /// additions are done in the same order as by the [`CostKind`], so the
+    /// costs are exactly those computed from scratch.
+    fn update_costs(&mut self, parser: &Z3Parser, prev_node_count: usize, changed: &RoaringBitmap) {
+        let new_nodes = prev_node_count..self.orig_graph.node_count();
+        match self.cost_model.shape() {
+            CostShape::Split(weight) => {
+                // Dependents come later, so walking backwards visits each
+                // node after all of its children are final.
+                for idx in changed.iter().rev() {
+                    let node = NodeIndex::new(idx as usize);
+                    let mut children: Vec<_> = self.orig_graph.neighbors_directed(node, Outgoing).collect();
+                    children.sort_unstable_by(|a, b| b.cmp(a));
+                    let mut cost = weight(&parser[InstIdx::from(idx as usize)]);
+                    for child in children {
+                        let child = &self.orig_graph[child];
+                        cost += child.cost / child.parent_count as f32;
+                    }
+                    self.orig_graph[node].cost = cost;
+                }
             }
-        }
-        // efficiently compute transitive closure with a vector of FixedBitSet's
-        let mut topo = Topo::new(petgraph::visit::Reversed(&self.orig_graph));
-        // assign topological orders to each node
-        let mut topo_ord = self.orig_graph.node_count() - 1;
-        while let Some(nx) = topo.next(petgraph::visit::Reversed(&self.orig_graph)) {
-            self.orig_graph[nx].topo_ord = topo_ord;
-            topo_ord = topo_ord.saturating_sub(1);
-        }
-        self.tr_closure = vec![RoaringBitmap::new(); self.orig_graph.node_count()];
-        // note that we are storing the bitsets's of each node index in topological order!
-        let mut topo = Topo::new(petgraph::visit::Reversed(&self.orig_graph));
-        let mut bitsets = self.tr_closure.as_mut_slice();
-        let mut ord = self.orig_graph.node_count() - 1;
-        while let Some((last, others)) = bitsets.split_last_mut() {
-            if let Some(nx) = topo.next(petgraph::visit::Reversed(&self.orig_graph)) {
-                last.insert(nx.index() as u32);
-                for pred in self.orig_graph.neighbors_directed(nx, Incoming) {
-                    let pred_topo_ord = self.orig_graph.node_weight(pred).unwrap().topo_ord;
-                    let pred_bitset = others.get_mut(pred_topo_ord).unwrap();
-                    *pred_bitset |= &*last;
+            CostShape::Inclusive => {
+                for idx in new_nodes {
+                    for ancestor in &self.ancestors[idx] {
+                        self.orig_graph[NodeIndex::new(ancestor as usize)].cost += 1.0;
+                    }
                 }
             }
-            bitsets = others;
-            ord = ord.saturating_sub(1);
-        }
-        log!("Finished computing transitive closure");
-        self.visible_graph = self.orig_graph.map(
-            |_, n| n.clone(),
-            |orig_graph_idx, e| EdgeType::Direct {
-                kind: e.clone(),
-                orig_graph_idx,
-            },
-        )
+            CostShape::Exclusive => {
+                // The dominators of a node are itself and those common to
+                // all of its parents. `changed` contains the parents of each
+                // of its nodes, so walking forwards visits them first.
+                let mut dominators: FxHashMap<u32, RoaringBitmap> = FxHashMap::default();
+                for idx in changed {
+                    let node = NodeIndex::new(idx as usize);
+                    let mut parents = self.orig_graph.neighbors_directed(node, Incoming).map(|p| &dominators[&(p.index() as u32)]);
+                    let mut own = parents.next().map(|first| parents.fold(first.clone(), |own, p| own & p)).unwrap_or_default();
+                    own.insert(idx);
+                    dominators.insert(idx, own);
+                }
+                for idx in new_nodes {
+                    for dominator in &dominators[&(idx as u32)] {
+                        self.orig_graph[NodeIndex::new(dominator as usize)].cost += 1.0;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the new nodes of [`extend`](Self::extend) to the visible graph,
+    /// keeping the nodes hidden by filters hidden. If a new node depends on
+    /// one which is not visible, the visible graph is reconnected with
+    /// [`retain_visible_nodes_and_reconnect`](Self::retain_visible_nodes_and_reconnect)
+    /// instead.
+    fn extend_visible_graph(&mut self, prev_node_count: usize, new_edges: Vec<EdgeIndex>) {
+        for node in self.visible_graph.node_weights_mut() {
+            let orig = &self.orig_graph[NodeIndex::from(node.inst_idx)];
+            node.cost = orig.cost;
+            node.cost_rank = orig.cost_rank;
+            node.branching_rank = orig.branching_rank;
+            node.child_count = orig.child_count;
+            node.useful = orig.useful;
+        }
+        let mut visible: FxHashMap<InstIdx, NodeIndex> = self.visible_graph.node_indices().map(|n| (self.visible_graph[n].inst_idx, n)).collect();
+        let all_visible = new_edges.iter().all(|&e| {
+            let (from, _) = self.orig_graph.edge_endpoints(e).unwrap();
+            usize::from(self.orig_graph[from].inst_idx) >= prev_node_count || visible.contains_key(&self.orig_graph[from].inst_idx)
+        });
+        if !all_visible {
+            self.retain_visible_nodes_and_reconnect();
+            return;
+        }
+        for node in self.orig_graph.node_weights().skip(prev_node_count) {
+            visible.insert(node.inst_idx, self.visible_graph.add_node(node.clone()));
+        }
+        for orig_graph_idx in new_edges {
+            let (from, to) = self.orig_graph.edge_endpoints(orig_graph_idx).unwrap();
+            let (from, to) = (visible[&InstIdx::from(from.index())], visible[&InstIdx::from(to.index())]);
+            let kind = self.orig_graph[orig_graph_idx].clone();
+            self.visible_graph.add_edge(from, to, EdgeType::Direct { kind, orig_graph_idx });
+        }
+    }
+
+    pub fn cost_model(&self) -> CostKind {
+        self.cost_model
+    }
+    /// Recomputes the node costs with a different model, this changes which
+    /// nodes [`keep_n_most_costly`](Self::keep_n_most_costly) keeps. Node
+    /// visibility is left unchanged.
+    pub fn set_cost_model(&mut self, parser: &Z3Parser, model: CostKind) {
+        if self.cost_model == model {
+            return;
+        }
+        self.cost_model = model;
+        let costs = parser.inst_costs(&model);
+        for node in self.orig_graph.node_weights_mut() {
+            node.cost = costs[node.inst_idx];
+        }
+        self.rank_nodes(&(0..self.orig_graph.node_count() as u32).collect());
+        for node in self.visible_graph.node_weights_mut() {
+            node.cost = costs[node.inst_idx];
+            node.cost_rank = self.orig_graph[NodeIndex::from(node.inst_idx)].cost_rank;
+        }
+    }
+
+    /// Ranks the nodes by cost and by number of children, in descending order
+    /// with ties broken by instantiation order (so that both are total
+    /// orders). Only the `changed` nodes, which may not have been ranked yet,
+    /// are moved, and only the ranks from the first one which moved on are
+    /// updated.
+    fn rank_nodes(&mut self, changed: &RoaringBitmap) {
+        let graph = &self.orig_graph;
+        let node = |idx: &InstIdx| &graph[NodeIndex::from(*idx)];
+        let by_cost = Self::rerank(&mut self.cost_ranked_node_indices, changed, |a, b| node(b).cost.total_cmp(&node(a).cost).then(a.cmp(b)));
+        let by_branching = Self::rerank(&mut self.branching_ranked_node_indices, changed, |a, b| node(b).child_count.cmp(&node(a).child_count).then(a.cmp(b)));
+        for (rank, idx) in self.cost_ranked_node_indices.iter().enumerate().skip(by_cost) {
+            self.orig_graph[NodeIndex::from(*idx)].cost_rank = rank;
+        }
+        for (rank, idx) in self.branching_ranked_node_indices.iter().enumerate().skip(by_branching) {
+            self.orig_graph[NodeIndex::from(*idx)].branching_rank = rank;
+        }
+    }
+
+    /// Sorts the `changed` nodes back into `ranked`, returning the first
+    /// rank which may have changed.
+    fn rerank(ranked: &mut Vec<InstIdx>, changed: &RoaringBitmap, order: impl Fn(&InstIdx, &InstIdx) -> std::cmp::Ordering) -> usize {
+        let is_changed = |idx: &InstIdx| changed.contains(usize::from(*idx) as u32);
+        let removed = ranked.iter().position(is_changed).unwrap_or(ranked.len());
+        ranked.retain(|idx| !is_changed(idx));
+        let mut moved: Vec<_> = changed.iter().map(|idx| InstIdx::from(idx as usize)).collect();
+        moved.sort_by(&order);
+        // Merges the two sorted runs in linear time.
+        ranked.extend(moved);
+        ranked.sort_by(&order);
+        removed.min(ranked.iter().position(is_changed).unwrap_or(ranked.len()))
     }
 
     fn add_node(&mut self, node_data: NodeData) {
@@ -852,8 +1026,8 @@ impl InstGraph {
         assert_eq!(usize::from(inst_idx), node.index());
     }
 
-    fn add_edge(&mut self, from: InstIdx, to: InstIdx, blame: &BlameKind) {
-        self.orig_graph.add_edge(from.into(), to.into(), blame.clone());
+    fn add_edge(&mut self, from: InstIdx, to: InstIdx, blame: &BlameKind) -> EdgeIndex {
+        self.orig_graph.add_edge(from.into(), to.into(), blame.clone())
     }
 
     pub fn get_node_info_map(&self) -> NodeInfoMap {
@@ -875,9 +1049,12 @@ impl InstGraph {
 pub struct NodeInfoMap; 
 
 impl NodeInfoMap {
+    /// The `cost` is that of the node under the current cost model, see
+    /// [`NodeData::cost`], or `None` if the graph holding it is unavailable.
     pub fn get_instantiation_info(
         &self,
         inst_idx: InstIdx,
+        cost: Option<f32>,
         parser: &Z3Parser,
         ignore_ids: bool,
     ) -> InstInfo {
@@ -895,6 +1072,7 @@ impl NodeInfoMap {
             .due_to_terms()
             .map(|eidx| eidx.with(&ctxt).to_string())
             .collect::<Vec<String>>();
+        let provenance = parser.rewrite_provenance(inst_idx);
         let inst_info = InstInfo {
             fingerprint: inst.fingerprint,
             inst_idx,
@@ -902,7 +1080,7 @@ impl NodeInfoMap {
                 .get_resulting_term()
                 .map(|rt| rt.with(&ctxt).to_string()),
             z3_gen: inst.z3_generation,
-            cost: inst.cost,
+            cost,
             mkind: match_.kind.clone(),
             quant_discovered: match_.kind.is_discovered(),
             formula: match_.kind.with(&ctxt).to_string(),
@@ -921,6 +1099,13 @@ impl NodeInfoMap {
                 .map(|eq| eq.with(&ctxt).to_string())
                 .collect(),
             dep_instantiations: Vec::new(),
+            rewrite_of: provenance.as_ref().map(|p| p.original.with(&ctxt).to_string()),
+            rewrite_results: provenance
+                .as_ref()
+                .map(|p| p.results.iter().map(|t| t.with(&ctxt).to_string()).collect())
+                .unwrap_or_default(),
+            rewrite_chain: provenance.as_ref().map(|p| p.chain.clone()).unwrap_or_default(),
+            rewrite_consumers: provenance.map(|p| p.consumers).unwrap_or_default(),
         };
         inst_info
     }
@@ -1,11 +1,24 @@
 use std::fmt::Debug;
 
-use crate::{Result, Error, FResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{Diagnostics, Error, FResult, ParserOptions, Result};
 use super::LogParser;
 
+pub mod budget;
+pub mod cost;
+pub mod diff;
+pub mod export;
 pub mod egraph;
+mod import;
 pub mod inst;
 pub mod inst_graph;
+pub mod proofs;
+mod quant_header;
+pub mod quirks;
+pub mod rewrites;
+pub mod search;
+pub mod snapshot;
 pub mod stack;
 pub mod terms;
 /// Original Z3 log parser. Works with Z3 v.4.12.1, should work with other versions
@@ -18,7 +31,11 @@ impl<T: Z3LogParser + Default> LogParser for T {
         first_byte == b'['
     }
 
-    fn process_line(&mut self, line: &str, line_no: usize) -> FResult<bool> {
+    fn set_options(&mut self, options: ParserOptions) {
+        self.diagnostics_mut().set_policy(options.diagnostics);
+    }
+
+    fn process_line(&mut self, line: &str, line_no: usize, byte_offset: usize) -> FResult<bool> {
         // Much faster than `split_whitespace` or `split(' ')` since it works on
         // [u8] instead of [char] and so doesn't need to convert to UTF-8.
         let mut split = line.split_ascii_whitespace();
@@ -55,17 +72,13 @@ impl<T: Z3LogParser + Default> LogParser for T {
         };
         match parse {
             Ok(()) => Ok(true),
-            Err(err) => {
-                eprintln!("Error parsing line {line_no} ({err:?}): {line:?}");
-                let fatal = err.as_fatal();
-                if std::env::var("SLP_TEST_MODE").is_ok() {
-                    panic!();
+            Err(err) => match err.as_fatal() {
+                Some(err) => Err(err),
+                None => {
+                    self.diagnostics_mut().report(line_no, byte_offset, line, err)?;
+                    Ok(true)
                 }
-                match fatal {
-                    Some(err) => Err(err),
-                    None => Ok(true),
-                }
-            }
+            },
         }
     }
 
@@ -81,9 +94,9 @@ pub trait Z3LogParser {
      and `l0` is the raw line (used only when )
     */
     fn version_info<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()>;
-    fn mk_quant<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()>;
-    fn mk_var<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()>;
-    fn mk_proof_app<'a>(&mut self, l: impl Iterator<Item = &'a str>, is_proof: bool) -> Result<()>;
+    fn mk_quant<'a>(&mut self, l: impl Iterator<Item = &'a str> + Clone) -> Result<()>;
+    fn mk_var<'a>(&mut self, l: impl Iterator<Item = &'a str> + Clone) -> Result<()>;
+    fn mk_proof_app<'a>(&mut self, l: impl Iterator<Item = &'a str> + Clone, is_proof: bool) -> Result<()>;
     fn attach_meaning<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()>;
     fn attach_var_names<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()>;
     fn attach_enode<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()>;
@@ -96,6 +109,9 @@ pub trait Z3LogParser {
     fn pop<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()>;
     fn eof(&mut self);
 
+    /// Where recoverable errors encountered while parsing are recorded.
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics;
+
     // unused in original parser
     fn decide_and_or<'a>(&mut self, _l: impl Iterator<Item = &'a str>) -> Result<()> {
         DEFAULT
@@ -124,7 +140,7 @@ pub trait Z3LogParser {
 }
 
 /// Type of solver and version number
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VersionInfo {
     solver: String,
     version: semver::Version,
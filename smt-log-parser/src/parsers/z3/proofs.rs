@@ -0,0 +1,67 @@
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use typed_index_collections::TiVec;
+
+use crate::{
+    items::{InstIdx, Instantiation, ProofIdx, ProofRule, ProofStep, TermIdx},
+    Result,
+};
+
+/// The proof DAG built from the `[mk-proof]` terms of the log.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Proofs {
+    pub(super) steps: TiVec<ProofIdx, ProofStep>,
+    term_to_step: FxHashMap<TermIdx, ProofIdx>,
+}
+
+impl Proofs {
+    /// Add a proof step for the proof term `term` with the given children.
+    /// Children which are proof terms are premises, a trailing non-proof
+    /// child is the conclusion.
+    pub(super) fn new_step(&mut self, term: TermIdx, rule: ProofRule, children: &[TermIdx]) -> Result<ProofIdx> {
+        let (conclusion, premises) = match children.split_last() {
+            Some((last, premises)) if !self.term_to_step.contains_key(last) => (Some(*last), premises),
+            _ => (None, children),
+        };
+        let premises = premises.iter().filter_map(|c| self.term_to_step.get(c).copied()).collect();
+        self.steps.raw.try_reserve(1)?;
+        let step = self.steps.push_and_get_key(ProofStep { term, rule, premises, conclusion, inst: None });
+        self.term_to_step.try_reserve(1)?;
+        self.term_to_step.insert(term, step);
+        Ok(step)
+    }
+
+    /// Link `quant-inst` steps to the instantiations whose `proof_id` refers
+    /// to either the step or its conclusion.
+    pub(super) fn link_insts(&mut self, insts: &TiVec<InstIdx, Instantiation>) {
+        let by_proof_id: FxHashMap<TermIdx, InstIdx> = insts
+            .iter_enumerated()
+            .filter_map(|(idx, inst)| Some((inst.get_resulting_term()?, idx)))
+            .collect();
+        for step in self.steps.iter_mut().filter(|s| s.rule == ProofRule::QuantInst) {
+            step.inst = by_proof_id
+                .get(&step.term)
+                .or_else(|| step.conclusion.and_then(|c| by_proof_id.get(&c)))
+                .copied();
+        }
+    }
+
+    pub fn steps(&self) -> &TiVec<ProofIdx, ProofStep> {
+        &self.steps
+    }
+    /// The proof step of a proof term.
+    pub fn step_of(&self, term: TermIdx) -> Option<ProofIdx> {
+        self.term_to_step.get(&term).copied()
+    }
+    /// All steps which `root` (transitively) depends on, including itself.
+    pub fn reachable(&self, root: ProofIdx) -> FxHashSet<ProofIdx> {
+        let mut seen = FxHashSet::default();
+        let mut todo = vec![root];
+        while let Some(step) = todo.pop() {
+            if seen.insert(step) {
+                todo.extend_from_slice(&self.steps[step].premises);
+            }
+        }
+        seen
+    }
+}
@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+
+use crate::{Error, Result};
+
+/// The tokens of a `[mk-quant]` or `[mk-lambda]` line after the term id:
+/// `<name> <num_vars> <pattern ids>* <body id>`.
+///
+/// Z3 prints the quantifier name (the `:qid`) as-is, so it may contain
+/// spaces, brackets and numbers (e.g. Gobra's
+/// `$Set[ShStruct2[Ref, Ref]]_prog.card_non_negative`). An unquoted name is
+/// therefore everything before the last token which is not a term id, that
+/// token being the number of variables. A name in the `|quoted|` form is
+/// taken up to the closing bar, without the bars.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct QuantHeader<'a> {
+    pub name: Cow<'a, str>,
+    pub num_vars: usize,
+    pub children: Vec<&'a str>,
+}
+
+impl<'a> QuantHeader<'a> {
+    pub fn parse(l: impl Iterator<Item = &'a str>) -> Result<Self> {
+        let tokens: Vec<_> = l.collect();
+        let first = *tokens.first().ok_or(Error::UnexpectedNewline)?;
+        let (name, rest) = if first.starts_with('|') {
+            // Don't treat the opening bar of a lone `|` as closing.
+            let ends_quote = |(i, t): &(usize, &&str)| t.ends_with('|') && (*i > 0 || t.len() > 1);
+            let end = tokens.iter().enumerate().find(ends_quote).map(|(i, _)| i);
+            let end = end.ok_or_else(|| Error::QuantNameNoBar(tokens.join(" ")))?;
+            let name = Self::join(&tokens[..=end]);
+            let name = match name {
+                Cow::Borrowed(name) => Cow::Borrowed(&name[1..name.len() - 1]),
+                Cow::Owned(name) => Cow::Owned(name[1..name.len() - 1].to_string()),
+            };
+            (name, &tokens[end + 1..])
+        } else {
+            let num_vars = tokens.iter().rposition(|t| !Self::is_term_id(t));
+            let num_vars = num_vars.filter(|&i| i > 0).ok_or_else(|| Error::QuantNoNumVars(tokens.join(" ")))?;
+            (Self::join(&tokens[..num_vars]), &tokens[num_vars..])
+        };
+        let (num_vars, children) = rest.split_first().ok_or(Error::UnexpectedNewline)?;
+        let num_vars = num_vars.parse::<usize>().map_err(|_| Error::QuantNoNumVars(format!("{name} {num_vars}")))?;
+        if children.is_empty() {
+            return Err(Error::UnexpectedNewline);
+        }
+        Ok(Self { name, num_vars, children: children.to_vec() })
+    }
+
+    /// Re-joins a name which was split on whitespace. Runs of whitespace in
+    /// the original name are collapsed into a single space.
+    fn join(tokens: &[&'a str]) -> Cow<'a, str> {
+        match tokens {
+            [token] => Cow::Borrowed(token),
+            tokens => Cow::Owned(tokens.join(" ")),
+        }
+    }
+
+    /// Does `token` have the form of a [`TermId`](crate::items::TermId), i.e.
+    /// `namespace#number` with both parts optional?
+    fn is_term_id(token: &str) -> bool {
+        token.find('#').is_some_and(|hash| token[hash + 1..].bytes().all(|b| b.is_ascii_digit()))
+    }
+}
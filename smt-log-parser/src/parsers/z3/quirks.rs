@@ -0,0 +1,134 @@
+use std::fmt;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// A known Z3 bug or change in behaviour which the parser works around.
+/// Results derived from a log in which a workaround fired might be skewed,
+/// see [`Z3Parser::quirks`](crate::Z3Parser::quirks).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Quirk {
+    /// `[attach-enode]` is emitted with a term id which was never created.
+    /// The line is ignored.
+    AttachEnodeUnknownTerm,
+    /// Very rarely an `[attach-enode]` is not emitted for a term which is
+    /// then used as an enode. The enode is created on first use.
+    MissingAttachEnode,
+    /// The two sides of an equality blamed by a match may have different
+    /// roots if one is an `if` term. The equality is not blamed. See
+    /// [Z3Prover/z3@faf1401](https://github.com/Z3Prover/z3/commit/faf14012ba18d21c1fcddbdc321ac127f019fa03).
+    IfTermRootMismatch,
+    /// A `[pop]` is not emitted before a `[push]` or `[pop]` at a lower
+    /// scope (seen in v4.8.17 and v4.11.2). The frame is conservatively
+    /// leaked and treated as always active.
+    MissingPop,
+}
+
+impl Quirk {
+    pub const ALL: [Self; 4] = [
+        Self::AttachEnodeUnknownTerm,
+        Self::MissingAttachEnode,
+        Self::IfTermRootMismatch,
+        Self::MissingPop,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::AttachEnodeUnknownTerm => "attach-enode-unknown-term",
+            Self::MissingAttachEnode => "missing-attach-enode",
+            Self::IfTermRootMismatch => "if-term-root-mismatch",
+            Self::MissingPop => "missing-pop",
+        }
+    }
+
+    /// The Z3 versions whose logs are affected.
+    pub fn versions(self) -> VersionRange {
+        match self {
+            Self::AttachEnodeUnknownTerm => VersionRange::exactly(Version::new(4, 8, 7)),
+            Self::MissingAttachEnode => VersionRange::exactly(Version::new(4, 12, 2)),
+            Self::IfTermRootMismatch => VersionRange { min: Some(Version::new(4, 12, 3)), max: None },
+            Self::MissingPop => VersionRange::default(),
+        }
+    }
+
+    /// Should the workaround be applied to a log of `version`? Quirks limited
+    /// to some versions never apply if the version is unknown.
+    pub fn applies_to(self, version: Option<&Version>) -> bool {
+        let range = self.versions();
+        match version {
+            Some(version) => range.contains(version),
+            None => range.min.is_none() && range.max.is_none(),
+        }
+    }
+}
+
+impl fmt::Display for Quirk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.versions())
+    }
+}
+
+/// An inclusive range of versions, unbounded where `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: Option<Version>,
+    pub max: Option<Version>,
+}
+
+impl VersionRange {
+    pub fn exactly(version: Version) -> Self {
+        Self { min: Some(version.clone()), max: Some(version) }
+    }
+    pub fn contains(&self, version: &Version) -> bool {
+        self.min.as_ref().is_none_or(|min| min <= version) && self.max.as_ref().is_none_or(|max| version <= max)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.min, &self.max) {
+            (None, None) => write!(f, "all versions"),
+            (Some(min), Some(max)) if min == max => write!(f, "v{min}"),
+            (Some(min), Some(max)) => write!(f, "v{min} to v{max}"),
+            (Some(min), None) => write!(f, "v{min} and later"),
+            (None, Some(max)) => write!(f, "up to v{max}"),
+        }
+    }
+}
+
+/// How often the workaround for each [`Quirk`] was applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quirks {
+    counts: [usize; Quirk::ALL.len()],
+}
+
+impl Quirks {
+    pub(super) fn fire(&mut self, quirk: Quirk) {
+        self.counts[quirk as usize] += 1;
+    }
+
+    pub fn count(&self, quirk: Quirk) -> usize {
+        self.counts[quirk as usize]
+    }
+    /// The quirks whose workaround was applied at least once, with how often.
+    pub fn fired(&self) -> impl Iterator<Item = (Quirk, usize)> + '_ {
+        Quirk::ALL.into_iter().map(|quirk| (quirk, self.count(quirk))).filter(|(_, count)| *count > 0)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.fired().next().is_none()
+    }
+}
+
+impl fmt::Display for Quirks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No Z3 quirks worked around");
+        }
+        write!(f, "Worked around Z3 quirks:")?;
+        for (quirk, count) in self.fired() {
+            let plural = if count == 1 { "" } else { "s" };
+            write!(f, "\n  {quirk}: {count} time{plural}")?;
+        }
+        Ok(())
+    }
+}
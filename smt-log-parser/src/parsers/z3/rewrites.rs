@@ -0,0 +1,129 @@
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::items::{InstIdx, TermIdx};
+
+use super::z3parser::Z3Parser;
+
+/// Provenance of theory-solving rewrites, built from
+/// [`MatchKind::rewrite_of`](crate::items::MatchKind::rewrite_of) once
+/// parsing has completed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Rewrites {
+    /// The rewrites applied to each term, in the order they happened.
+    of_term: FxHashMap<TermIdx, Vec<InstIdx>>,
+    /// The rewrite which produced each term.
+    produced_by: FxHashMap<TermIdx, InstIdx>,
+    /// The instantiations triggered by terms which a rewrite produced.
+    consumers: FxHashMap<InstIdx, Vec<InstIdx>>,
+}
+
+/// How a term was rewritten by theory solving, see
+/// [`Z3Parser::rewrite_provenance`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewriteProvenance {
+    /// The term before any of the rewrites were applied.
+    pub original: TermIdx,
+    /// The rewriting instantiations in the order they were applied, each
+    /// rewrites a result of the previous one.
+    pub chain: Vec<InstIdx>,
+    /// The terms produced by the last rewrite of the chain.
+    pub results: Vec<TermIdx>,
+    /// The instantiations triggered by a term produced by any rewrite of the
+    /// chain.
+    pub consumers: Vec<InstIdx>,
+}
+
+impl Rewrites {
+    pub(super) fn compute(parser: &Z3Parser) -> Self {
+        let mut rewrites = Self::default();
+        for (idx, inst) in parser.insts.insts.iter_enumerated() {
+            let match_ = &parser.insts[inst.match_];
+            for (_, enode) in match_.due_to_enodes() {
+                let Some(created_by) = parser[enode].created_by else {
+                    continue;
+                };
+                if parser.insts[parser.insts[created_by].match_].kind.rewrite_of().is_some() {
+                    let consumers = rewrites.consumers.entry(created_by).or_default();
+                    if consumers.last() != Some(&idx) {
+                        consumers.push(idx);
+                    }
+                }
+            }
+            let Some(rewrite_of) = match_.kind.rewrite_of() else {
+                continue;
+            };
+            rewrites.of_term.entry(rewrite_of).or_default().push(idx);
+            for result in parser.rewrite_results(idx) {
+                rewrites.produced_by.entry(result).or_insert(idx);
+            }
+        }
+        rewrites
+    }
+
+    /// Every term which was rewritten together with the rewrites applied to
+    /// it.
+    pub fn rewritten_terms(&self) -> impl Iterator<Item = (TermIdx, &[InstIdx])> + '_ {
+        self.of_term.iter().map(|(&term, insts)| (term, insts.as_slice()))
+    }
+    /// The rewrite which produced `term`, if any.
+    pub fn produced_by(&self, term: TermIdx) -> Option<InstIdx> {
+        self.produced_by.get(&term).copied()
+    }
+    /// The instantiations triggered by terms produced by the rewrite `inst`.
+    pub fn consumers(&self, inst: InstIdx) -> &[InstIdx] {
+        self.consumers.get(&inst).map_or(&[], Vec::as_slice)
+    }
+    /// The rewrites applied to `term`.
+    pub fn of_term(&self, term: TermIdx) -> &[InstIdx] {
+        self.of_term.get(&term).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl Z3Parser {
+    /// The rewrite provenance of all theory-solving rewrites.
+    pub fn rewrites(&self) -> &Rewrites {
+        &self.rewrites
+    }
+
+    /// The terms produced by the theory-solving rewrite `inst`: the terms it
+    /// yielded and, if its resulting term is an equality, the rewritten side.
+    pub fn rewrite_results(&self, inst: InstIdx) -> Vec<TermIdx> {
+        let inst = &self.insts[inst];
+        let mut results: Vec<_> = inst.yields_terms.iter().map(|&e| self[e].owner).collect();
+        if let Some(result) = inst.get_resulting_term() {
+            let result = &self[result];
+            let is_eq = result.kind.app_name().is_some_and(|name| matches!(&self.strings[name], "=" | "~"));
+            if let (true, [_, rhs]) = (is_eq, &*result.child_ids) {
+                results.push(*rhs);
+            }
+        }
+        let mut seen = FxHashSet::default();
+        results.retain(|t| seen.insert(*t));
+        results
+    }
+
+    /// The chain of theory-solving rewrites which `inst` is part of, `None`
+    /// if `inst` is not a rewrite. The chain is followed back to the
+    /// original term and forward along the first rewrite of each result.
+    pub fn rewrite_provenance(&self, inst: InstIdx) -> Option<RewriteProvenance> {
+        let kind = |i: InstIdx| &self.insts[self.insts[i].match_].kind;
+        let mut original = kind(inst).rewrite_of()?;
+        let mut chain = vec![inst];
+        while let Some(prev) = self.rewrites.produced_by(original).filter(|&p| p < chain[0]) {
+            chain.insert(0, prev);
+            original = kind(prev).rewrite_of().unwrap();
+        }
+        let mut last = inst;
+        let mut results = self.rewrite_results(last);
+        while let Some(next) = results.iter().flat_map(|&r| self.rewrites.of_term(r)).copied().find(|&n| n > last) {
+            chain.push(next);
+            last = next;
+            results = self.rewrite_results(last);
+        }
+        let mut consumers: Vec<_> = chain.iter().flat_map(|&r| self.rewrites.consumers(r)).copied().collect();
+        consumers.sort_unstable();
+        consumers.dedup();
+        Some(RewriteProvenance { original, chain, results, consumers })
+    }
+}
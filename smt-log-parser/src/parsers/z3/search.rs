@@ -0,0 +1,204 @@
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use typed_index_collections::TiVec;
+
+use crate::{
+    items::{
+        AssignIdx, Assignment, CaseSplit, CaseSplitIdx, CheckIdx, Conflict, ConflictIdx, Decision, DecisionIdx, ENodeIdx,
+        InstIdx, Justification, Literal, ResolveStep, StackIdx, TermIdx,
+    },
+    Error, Result,
+};
+
+use super::stack::Stack;
+
+/// The CDCL search of the solver: decisions, case splits, assignments,
+/// conflicts and their resolution. Decisions and case splits are tied to the
+/// stack frames they were made in, so backtracking (popping frames) also
+/// backtracks the decision and case split trees.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchTrace {
+    decisions: TiVec<DecisionIdx, Decision>,
+    assignments: TiVec<AssignIdx, Assignment>,
+    conflicts: TiVec<ConflictIdx, Conflict>,
+    case_splits: TiVec<CaseSplitIdx, CaseSplit>,
+    /// Decisions which may still be in effect, innermost last.
+    decision_stack: Vec<DecisionIdx>,
+    /// Case splits which may still be in effect, innermost last.
+    case_split_stack: Vec<CaseSplitIdx>,
+}
+
+/// Pop the entries of `open` whose frame has been popped, returns the
+/// innermost remaining one.
+fn innermost<I: Copy>(open: &mut Vec<I>, frame: impl Fn(I) -> Option<StackIdx>, stack: &Stack) -> Option<I> {
+    while let Some(&idx) = open.last() {
+        if frame(idx).is_none_or(|f| stack.stack_frames[f].active) {
+            return Some(idx);
+        }
+        open.pop();
+    }
+    None
+}
+
+impl SearchTrace {
+    /// The innermost decision which has not been backtracked.
+    fn current_decision(&mut self, stack: &Stack) -> Option<DecisionIdx> {
+        innermost(&mut self.decision_stack, |d| self.decisions[d].frame, stack)
+    }
+    /// The innermost case split branch which has not been backtracked.
+    pub(super) fn current_case_split(&mut self, stack: &Stack) -> Option<CaseSplitIdx> {
+        innermost(&mut self.case_split_stack, |c| self.case_splits[c].frame, stack)
+    }
+
+    pub(super) fn new_case_split(
+        &mut self,
+        term: TermIdx,
+        branch: TermIdx,
+        check: Option<CheckIdx>,
+        stack: &Stack,
+    ) -> Result<()> {
+        let parent = self.current_case_split(stack);
+        self.case_splits.raw.try_reserve(1)?;
+        let case_split = self.case_splits.push_and_get_key(CaseSplit {
+            term,
+            branch,
+            parent,
+            children: Vec::new(),
+            frame: stack.active_frame(),
+            check,
+        });
+        if let Some(parent) = parent {
+            let children = &mut self.case_splits[parent].children;
+            children.try_reserve(1)?;
+            children.push(case_split);
+        }
+        self.case_split_stack.try_reserve(1)?;
+        self.case_split_stack.push(case_split);
+        Ok(())
+    }
+
+    pub(super) fn new_decision(&mut self, lit: Literal, check: Option<CheckIdx>, stack: &Stack) -> Result<()> {
+        let parent = self.current_decision(stack);
+        self.decisions.raw.try_reserve(1)?;
+        let decision = self.decisions.push_and_get_key(Decision {
+            lit,
+            parent,
+            children: Vec::new(),
+            frame: stack.active_frame(),
+            check,
+            conflicts: Vec::new(),
+        });
+        if let Some(parent) = parent {
+            let children = &mut self.decisions[parent].children;
+            children.try_reserve(1)?;
+            children.push(decision);
+        }
+        self.decision_stack.try_reserve(1)?;
+        self.decision_stack.push(decision);
+        Ok(())
+    }
+
+    pub(super) fn new_assignment(
+        &mut self,
+        lit: Literal,
+        is_decision: bool,
+        justification: Justification,
+        stack: &Stack,
+    ) -> Result<()> {
+        let decision = self.current_decision(stack);
+        self.assignments.raw.try_reserve(1)?;
+        self.assignments.push(Assignment { lit, is_decision, justification, decision });
+        Ok(())
+    }
+
+    pub(super) fn new_conflict(&mut self, lits: Box<[Literal]>, check: Option<CheckIdx>, stack: &Stack) -> Result<()> {
+        let decision = self.current_decision(stack);
+        self.conflicts.raw.try_reserve(1)?;
+        let conflict = self.conflicts.push_and_get_key(Conflict { lits, decision, check, steps: Vec::new() });
+        if let Some(decision) = decision {
+            let conflicts = &mut self.decisions[decision].conflicts;
+            conflicts.try_reserve(1)?;
+            conflicts.push(conflict);
+        }
+        Ok(())
+    }
+
+    /// Add a resolution step to the latest conflict.
+    pub(super) fn resolve(&mut self, step: ResolveStep) -> Result<()> {
+        let conflict = self.conflicts.last_mut().ok_or(Error::ResolveWithoutConflict)?;
+        conflict.steps.try_reserve(1)?;
+        conflict.steps.push(step);
+        Ok(())
+    }
+
+    pub fn decisions(&self) -> &TiVec<DecisionIdx, Decision> {
+        &self.decisions
+    }
+    pub fn assignments(&self) -> &TiVec<AssignIdx, Assignment> {
+        &self.assignments
+    }
+    pub fn conflicts(&self) -> &TiVec<ConflictIdx, Conflict> {
+        &self.conflicts
+    }
+    pub fn case_splits(&self) -> &TiVec<CaseSplitIdx, CaseSplit> {
+        &self.case_splits
+    }
+
+    /// The decisions made at the base level, the roots of the decision tree.
+    pub fn roots(&self) -> impl Iterator<Item = DecisionIdx> + '_ {
+        self.decisions.iter_enumerated().filter(|(_, d)| d.parent.is_none()).map(|(idx, _)| idx)
+    }
+    /// The decisions from the root of the tree down to (and including)
+    /// `decision`.
+    pub fn path_to(&self, decision: DecisionIdx) -> Vec<DecisionIdx> {
+        let mut path: Vec<_> = std::iter::successors(Some(decision), |&d| self.decisions[d].parent).collect();
+        path.reverse();
+        path
+    }
+    /// The number of decisions above `decision` in the tree.
+    pub fn depth(&self, decision: DecisionIdx) -> usize {
+        std::iter::successors(self.decisions[decision].parent, |&d| self.decisions[d].parent).count()
+    }
+    /// The conflicts found in the subtree below (and including) `decision`.
+    pub fn subtree_conflicts(&self, decision: DecisionIdx) -> Vec<ConflictIdx> {
+        let mut conflicts = Vec::new();
+        let mut todo = vec![decision];
+        while let Some(decision) = todo.pop() {
+            let decision = &self.decisions[decision];
+            conflicts.extend_from_slice(&decision.conflicts);
+            todo.extend_from_slice(&decision.children);
+        }
+        conflicts.sort_unstable();
+        conflicts
+    }
+
+    /// The instantiations which produced the literals of a conflict
+    /// (including those reached while resolving it), each reported once.
+    /// `created_by` maps an enode to the instantiation which created it.
+    pub fn conflict_insts(
+        &self,
+        conflict: ConflictIdx,
+        created_by: impl Fn(ENodeIdx) -> Option<InstIdx>,
+    ) -> Vec<InstIdx> {
+        let mut seen = FxHashSet::default();
+        self.conflicts[conflict]
+            .all_lits()
+            .filter_map(|lit| lit.enode.and_then(&created_by))
+            .filter(|inst| seen.insert(*inst))
+            .collect()
+    }
+    /// For every instantiation which produced a literal involved in a
+    /// conflict, the number of conflicts it was involved in. Sorted by the
+    /// number of conflicts, most first.
+    pub fn insts_in_conflicts(&self, created_by: impl Fn(ENodeIdx) -> Option<InstIdx>) -> Vec<(InstIdx, usize)> {
+        let mut counts: FxHashMap<InstIdx, usize> = FxHashMap::default();
+        for conflict in self.conflicts.keys() {
+            for inst in self.conflict_insts(conflict, &created_by) {
+                *counts.entry(inst).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts
+    }
+}
@@ -0,0 +1,84 @@
+//! Binary snapshots of a parsed log, optionally together with its computed
+//! [`InstGraph`]. Loading a snapshot is much faster than parsing the original
+//! log again.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::SnapshotError;
+
+use super::inst_graph::InstGraph;
+use super::z3parser::Z3Parser;
+
+/// Every snapshot starts with these bytes, followed by the format version.
+const MAGIC: &[u8; 8] = b"SLPSNAP\0";
+/// The version of the snapshot format. Must be bumped whenever the serialized
+/// layout of `Z3Parser` or `InstGraph` (or any type they contain) changes.
+/// Version 1 covered several unreleased layouts, snapshots written by any of
+/// them are rejected as a version mismatch.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    parser: &'a Z3Parser,
+    graph: Option<&'a InstGraph>,
+    complete: bool,
+}
+
+/// The contents of a snapshot read with
+/// [`Z3Parser::load_snapshot`].
+#[derive(Deserialize)]
+pub struct Snapshot {
+    pub parser: Z3Parser,
+    pub graph: Option<InstGraph>,
+    /// Whether the whole log had been parsed when the snapshot was written.
+    pub complete: bool,
+}
+
+impl Z3Parser {
+    /// Write the parser state (and optionally a graph built from it) as a
+    /// snapshot. `complete` records whether the whole log was parsed, so that
+    /// a snapshot of a partially parsed log is not mistaken for a full one.
+    pub fn save_snapshot(&self, graph: Option<&InstGraph>, complete: bool, mut writer: impl Write) -> Result<(), SnapshotError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        bincode::serialize_into(&mut writer, &SnapshotRef { parser: self, graph, complete })?;
+        writer.flush()?;
+        Ok(())
+    }
+    /// Read a snapshot previously written with
+    /// [`save_snapshot`](Self::save_snapshot). Fails with
+    /// [`SnapshotError::VersionMismatch`] if it was written by a version of
+    /// this crate with a different snapshot format.
+    pub fn load_snapshot(mut reader: impl Read) -> Result<Snapshot, SnapshotError> {
+        let mut magic = [0; MAGIC.len()];
+        let mut version = [0; 4];
+        let header = reader.read_exact(&mut magic).and_then(|_| reader.read_exact(&mut version));
+        match header {
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Err(SnapshotError::NotASnapshot),
+            header => header?,
+        }
+        if &magic != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let found = u32::from_le_bytes(version);
+        if found != SNAPSHOT_VERSION {
+            return Err(SnapshotError::VersionMismatch { found, expected: SNAPSHOT_VERSION });
+        }
+        Ok(bincode::deserialize_from(reader)?)
+    }
+
+    /// Convenience wrapper around [`save_snapshot`](Self::save_snapshot)
+    /// which creates (or truncates) the file at the given path.
+    pub fn save_snapshot_file<P: AsRef<Path>>(&self, graph: Option<&InstGraph>, complete: bool, p: P) -> Result<(), SnapshotError> {
+        self.save_snapshot(graph, complete, BufWriter::new(File::create(p)?))
+    }
+    /// Convenience wrapper around [`load_snapshot`](Self::load_snapshot)
+    /// which reads from the file at the given path.
+    pub fn load_snapshot_file<P: AsRef<Path>>(p: P) -> Result<Snapshot, SnapshotError> {
+        Self::load_snapshot(BufReader::new(File::open(p)?))
+    }
+}
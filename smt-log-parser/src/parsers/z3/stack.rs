@@ -1,8 +1,11 @@
+use serde::{Deserialize, Serialize};
 use typed_index_collections::TiVec;
 
 use crate::{items::StackIdx, Result, Error};
 
-#[derive(Debug, Default)]
+use super::quirks::{Quirk, Quirks};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Stack {
     pub(super) stack: Vec<StackIdx>,
     pub(super) stack_frames: TiVec<StackIdx, StackFrame>,
@@ -21,7 +24,7 @@ impl Stack {
         self.stack_frames[idx].active = active;
         Some(idx)
     }
-    fn ensure_height(&mut self, height: usize) -> Result<()> {
+    fn ensure_height(&mut self, height: usize, quirks: &mut Quirks) -> Result<()> {
         let mut res = Ok(());
         // Neither condition should hold, but handle it as best we can.
         while height > self.stack.len() {
@@ -30,25 +33,23 @@ impl Stack {
             self.add_frame()?;
         }
         while height < self.stack.len() {
-            // This can happen when pushing a new frame in e.g. z3 v4.8.17 and
-            // v4.11.2.
-            // It seems that there is a bug where the pop doesn't get emitted
-            // and so we need to conservatively leak the frame and treat it as
+            // See `Quirk::MissingPop`, the frame is leaked and treated as
             // always active.
+            quirks.fire(Quirk::MissingPop);
             self.remove_frame(true);
         }
         res
     }
 
-    pub(super) fn new_frame(&mut self, idx: usize) -> Result<()> {
-        let res = self.ensure_height(idx);
+    pub(super) fn new_frame(&mut self, idx: usize, quirks: &mut Quirks) -> Result<()> {
+        let res = self.ensure_height(idx, quirks);
         self.add_frame()?;
         res
     }
 
-    pub(super) fn pop_frames(&mut self, count: usize, idx: usize) -> Result<()> {
+    pub(super) fn pop_frames(&mut self, count: usize, idx: usize, quirks: &mut Quirks) -> Result<()> {
         debug_assert!(0 < count && count <= idx);
-        let res = self.ensure_height(idx);
+        let res = self.ensure_height(idx, quirks);
         for _ in 0..count {
             self.remove_frame(false).ok_or(Error::StackFrameNotPushed)?;
         }
@@ -60,7 +61,7 @@ impl Stack {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StackFrame {
     pub active: bool,
 }
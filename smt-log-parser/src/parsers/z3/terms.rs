@@ -1,4 +1,5 @@
 use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use typed_index_collections::TiVec;
 
 use crate::{
@@ -6,12 +7,15 @@ use crate::{
     items::{StringTable, Term, TermId, TermIdToIdxMap, TermIdx, TermKind, Meaning, QuantIdx}
 };
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Terms {
     term_id_map: TermIdToIdxMap,
     terms: TiVec<TermIdx, Term>,
     meanings: FxHashMap<TermIdx, Meaning>,
     parsed_terms: Option<TermIdx>,
+    /// The id and the rest of the line of the first term created, used to
+    /// detect solver restarts.
+    first_term: Option<(TermId, Box<[String]>)>,
 
     synthetic_terms: FxHashMap<(Term, Option<Meaning>), TermIdx>,
 }
@@ -23,6 +27,7 @@ impl Terms {
             terms: TiVec::new(),
             meanings: FxHashMap::default(),
             parsed_terms: None,
+            first_term: None,
 
             synthetic_terms: FxHashMap::default(),
         }
@@ -38,6 +43,22 @@ impl Terms {
         Ok(idx)
     }
 
+    /// Would creating a term with this id (and the rest of its line) mean that
+    /// the solver restarted? Z3 reuses the ids of deleted terms, but never
+    /// recreates the first term it creates (usually `true`), which lives until
+    /// it exits. So only the same id for a different term is not a restart.
+    pub(super) fn is_restart<'a>(&self, id: &TermId, rest: impl Iterator<Item = &'a str>) -> bool {
+        self.first_term
+            .as_ref()
+            .is_some_and(|(first_id, first_rest)| first_id == id && rest.eq(first_rest.iter().map(String::as_str)))
+    }
+    /// Remember the first term of the session, see [`Self::is_restart`].
+    pub(super) fn set_first_term<'a>(&mut self, id: &TermId, rest: impl Iterator<Item = &'a str>) {
+        if self.first_term.is_none() {
+            self.first_term = Some((*id, rest.map(str::to_string).collect()));
+        }
+    }
+
     pub(super) fn parse_id(
         &self,
         strings: &mut StringTable,
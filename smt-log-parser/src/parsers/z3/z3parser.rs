@@ -1,21 +1,30 @@
+use fxhash::FxHashSet;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
 use typed_index_collections::TiVec;
 
 use crate::{
-    Error, Result,
+    Diagnostics, Error, Result,
     items::*,
     parsers::z3::{VersionInfo, Z3LogParser},
 };
 
 use super::{
-    egraph::{EGraph, ENode},
+    cost::{parents, CostModel, Costs},
+    egraph::{EGraph, ENode, EqualityMismatches, MatchEqualityMismatch},
     inst::Insts,
+    proofs::Proofs,
+    quant_header::QuantHeader,
+    quirks::{Quirk, Quirks},
+    rewrites::Rewrites,
+    search::SearchTrace,
     stack::Stack,
     terms::Terms,
 };
 
 /// A parser for Z3 log files. Use one of the various `Z3Parser::from_*` methods
 /// to construct this parser.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Z3Parser {
     pub(super) version_info: Option<VersionInfo>,
     pub(super) terms: Terms,
@@ -27,8 +36,26 @@ pub struct Z3Parser {
 
     pub(super) egraph: EGraph,
     pub(super) stack: Stack,
+    /// Equality lookups which are affected by respecting stack frames.
+    pub(super) equality_mismatches: Vec<MatchEqualityMismatch>,
+
+    pub(super) checks: TiVec<CheckIdx, Check>,
+    /// The check which is currently running, if any.
+    pub(super) active_check: Option<CheckIdx>,
+
+    pub(super) search: SearchTrace,
+    pub(super) proofs: Proofs,
+    pub(super) rewrites: Rewrites,
+    pub(super) quirks: Quirks,
 
     pub strings: StringTable,
+
+    /// Earlier sessions found in the same log, see
+    /// [`into_sessions`](Self::into_sessions).
+    pub(super) sessions: Vec<Z3Parser>,
+    /// Not included in snapshots, a loaded parser reports no errors.
+    #[serde(skip)]
+    pub(super) diagnostics: Diagnostics,
 }
 
 impl Default for Z3Parser {
@@ -42,12 +69,70 @@ impl Default for Z3Parser {
             inst_stack: Default::default(),
             egraph: Default::default(),
             stack: Default::default(),
+            equality_mismatches: Vec::new(),
+            checks: Default::default(),
+            active_check: None,
+            search: Default::default(),
+            proofs: Default::default(),
+            rewrites: Default::default(),
+            quirks: Default::default(),
             strings,
+            sessions: Vec::new(),
+            diagnostics: Default::default(),
         }
     }
 }
 
 impl Z3Parser {
+    /// The recoverable errors encountered while parsing the log.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+    /// The number of independent solver runs found in the log so far. A new
+    /// session starts when a second `[tool-version]` line is seen or when the
+    /// term id space is reset, which happens when multiple solver instances
+    /// write to the same log file one after the other. This parser always
+    /// holds the state of the latest session.
+    pub fn session_count(&self) -> usize {
+        self.sessions.len() + 1
+    }
+    /// The completed sessions before the current one, in the order they
+    /// appear in the log.
+    pub fn previous_sessions(&self) -> &[Z3Parser] {
+        &self.sessions
+    }
+    /// Split into one independent parser per session in the order they
+    /// appear in the log.
+    pub fn into_sessions(mut self) -> Vec<Z3Parser> {
+        let mut sessions = std::mem::take(&mut self.sessions);
+        sessions.push(self);
+        sessions
+    }
+    /// Finish the current session and continue parsing into a fresh state.
+    /// Diagnostics are kept since they refer to lines of the whole log.
+    fn new_session(&mut self) {
+        let mut session = std::mem::take(self);
+        session.eof();
+        self.sessions = std::mem::take(&mut session.sessions);
+        self.diagnostics = std::mem::take(&mut session.diagnostics);
+        self.version_info = session.version_info.clone();
+        self.sessions.push(session);
+    }
+    /// Parse the id of a new term from the start of its line, starting a new
+    /// session if the solver restarted.
+    fn parse_new_term_id<'a>(&mut self, l: &mut (impl Iterator<Item = &'a str> + Clone)) -> Result<TermId> {
+        let id = l.next().ok_or(Error::UnexpectedNewline)?;
+        let term_id = TermId::parse(&mut self.strings, id)?;
+        if !self.terms.is_restart(&term_id, l.clone()) {
+            self.terms.set_first_term(&term_id, l.clone());
+            return Ok(term_id);
+        }
+        self.new_session();
+        let term_id = TermId::parse(&mut self.strings, id)?;
+        self.terms.set_first_term(&term_id, l.clone());
+        Ok(term_id)
+    }
+
     pub fn version_info(&self) -> Option<&VersionInfo> {
         self.version_info.as_ref()
     }
@@ -57,14 +142,22 @@ impl Z3Parser {
     pub fn is_ge_version(&self, major: u64, minor: u64, patch: u64) -> bool {
         self.version_info.as_ref().is_some_and(|v| v.version >= semver::Version::new(major, minor, patch))
     }
+    /// Should the workaround for `quirk` be applied to this log?
+    pub fn quirk_applies(&self, quirk: Quirk) -> bool {
+        quirk.applies_to(self.version_info.as_ref().map(|v| &v.version))
+    }
+    /// The workarounds for Z3 bugs which were applied while parsing this
+    /// session, results may be skewed if any were.
+    pub fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
 
     pub fn parse_existing_enode(&mut self, id: &str) -> Result<ENodeIdx> {
         let idx = self.terms.parse_existing_id(&mut self.strings, id)?;
         let enode = self.egraph.get_enode(idx, &self.stack);
-        if self.is_version(4, 12, 2) && enode.is_err() {
-            // Very rarely in version 4.12.2, an `[attach-enode]` is not emitted. Create it here.
-            // TODO: log somewhere when this happens.
-            self.egraph.new_enode(None, idx, None, &self.stack)?;
+        if enode.is_err() && self.quirk_applies(Quirk::MissingAttachEnode) {
+            self.quirks.fire(Quirk::MissingAttachEnode);
+            self.egraph.new_enode(None, idx, None, self.active_check, &self.stack)?;
             return self.egraph.get_enode(idx, &self.stack);
         }
         enode
@@ -180,6 +273,37 @@ impl Z3Parser {
     ) -> impl Iterator<Item = &'s str> + 'a {
         l.take_while(move |elem| *elem != end)
     }
+    /// Parse a literal of the form `#id` or `(not #id)` starting with the
+    /// token `first`, returns `None` if `first` does not start a literal.
+    fn gobble_literal<'a>(&mut self, first: &'a str, l: &mut impl Iterator<Item = &'a str>) -> Result<Option<Literal>> {
+        let (id, value) = if first == "(not" {
+            let id = l.next().ok_or(Error::UnexpectedNewline)?;
+            let id = id.strip_suffix(')').ok_or_else(|| Error::InvalidLiteral(id.to_string()))?;
+            (id, false)
+        } else if first.contains('#') {
+            (first, true)
+        } else {
+            return Ok(None);
+        };
+        let term = self.terms.parse_existing_id(&mut self.strings, id)?;
+        let enode = self.egraph.get_enode(term, &self.stack).ok();
+        Ok(Some(Literal { term, value, enode }))
+    }
+    fn expect_literal<'a>(&mut self, l: &mut impl Iterator<Item = &'a str>) -> Result<Literal> {
+        let first = l.next().ok_or(Error::UnexpectedNewline)?;
+        self.gobble_literal(first, l)?.ok_or_else(|| Error::InvalidLiteral(first.to_string()))
+    }
+    /// Parse all remaining literals, skipping tokens which are not literals.
+    fn gobble_literals<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<Box<[Literal]>> {
+        let mut lits = Vec::new();
+        while let Some(first) = l.next() {
+            if let Some(lit) = self.gobble_literal(first, &mut l)? {
+                lits.try_reserve(1)?;
+                lits.push(lit);
+            }
+        }
+        Ok(lits.into_boxed_slice())
+    }
     fn expect_completed<'s>(mut l: impl Iterator<Item = &'s str>) -> Result<()> {
         l.next().map_or(Ok(()), |more| Err(Error::ExpectedNewline(more.to_string())))
     }
@@ -192,29 +316,18 @@ impl Z3LogParser for Z3Parser {
         // Return if there is unexpectedly more data
         Self::expect_completed(l)?;
         let version = semver::Version::parse(version)?;
-        println!("{solver} {version}");
+        if self.version_info.is_some() {
+            self.new_session();
+        }
         self.version_info = Some(VersionInfo { solver, version });
         Ok(())
     }
 
-    fn mk_quant<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
-        let full_id = l
-            .next()
-            .ok_or(Error::UnexpectedNewline)?;
-        let full_id = TermId::parse(&mut self.strings, full_id)?;
-        let mut quant_name = std::borrow::Cow::Borrowed(l.next().ok_or(Error::UnexpectedNewline)?);
-        let mut num_vars_str = l.next().ok_or(Error::UnexpectedNewline)?;
-        let mut num_vars = num_vars_str.parse::<usize>();
-        // The name may contain spaces... TODO: PR to add quotes around name when logging in z3
-        while num_vars.is_err() {
-            quant_name = std::borrow::Cow::Owned(format!("{quant_name} {num_vars_str}"));
-            num_vars_str = l.next().ok_or(Error::UnexpectedNewline)?;
-            num_vars = num_vars_str.parse::<usize>();
-        }
-        let quant_name = QuantKind::parse(&mut self.strings, &*quant_name);
-        let num_vars = num_vars.unwrap();
-        let child_ids = self.gobble_children(l)?;
-        assert!(!child_ids.is_empty());
+    fn mk_quant<'a>(&mut self, mut l: impl Iterator<Item = &'a str> + Clone) -> Result<()> {
+        let full_id = self.parse_new_term_id(&mut l)?;
+        let QuantHeader { name, num_vars, children } = QuantHeader::parse(l)?;
+        let quant_name = QuantKind::parse(&mut self.strings, &name);
+        let child_ids = self.gobble_children(children.into_iter())?;
         let qidx = self.quantifiers.next_key();
         let term = Term {
             id: Some(full_id),
@@ -227,7 +340,6 @@ impl Z3LogParser for Z3Parser {
             kind: quant_name,
             term: Some(tidx),
             instances: Vec::new(),
-            cost: 0.0,
             vars: None,
         };
         self.quantifiers.raw.try_reserve(1)?;
@@ -236,11 +348,8 @@ impl Z3LogParser for Z3Parser {
         Ok(())
     }
 
-    fn mk_var<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
-        let full_id = l
-            .next()
-            .ok_or(Error::UnexpectedNewline)?;
-        let full_id = TermId::parse(&mut self.strings, full_id)?;
+    fn mk_var<'a>(&mut self, mut l: impl Iterator<Item = &'a str> + Clone) -> Result<()> {
+        let full_id = self.parse_new_term_id(&mut l)?;
         let kind = l.next().ok_or(Error::UnexpectedNewline)?;
         let kind = TermKind::parse_var(kind)?;
         // Return if there is unexpectedly more data
@@ -256,23 +365,24 @@ impl Z3LogParser for Z3Parser {
 
     fn mk_proof_app<'a>(
         &mut self,
-        mut l: impl Iterator<Item = &'a str>,
+        mut l: impl Iterator<Item = &'a str> + Clone,
         is_proof: bool,
     ) -> Result<()> {
-        let full_id = l
-            .next()
-            .ok_or(Error::UnexpectedNewline)?;
-        let full_id = TermId::parse(&mut self.strings, full_id)?;
-        let name = self.strings.get_or_intern(l.next().ok_or(Error::UnexpectedNewline)?);
+        let full_id = self.parse_new_term_id(&mut l)?;
+        let name = l.next().ok_or(Error::UnexpectedNewline)?;
+        let rule = is_proof.then(|| ProofRule::parse(&mut self.strings, name));
+        let name = self.strings.get_or_intern(name);
         let kind = TermKind::parse_proof_app(is_proof, name);
-        // TODO: add rewrite, monotonicity cases
         let child_ids = self.gobble_children(l)?;
         let term = Term {
             id: Some(full_id),
             kind,
             child_ids,
         };
-        self.terms.new_term(term)?;
+        let tidx = self.terms.new_term(term)?;
+        if let Some(rule) = rule {
+            self.proofs.new_step(tidx, rule, &self.terms[tidx].child_ids)?;
+        }
         Ok(())
     }
 
@@ -300,8 +410,8 @@ impl Z3LogParser for Z3Parser {
         let id = l.next().ok_or(Error::UnexpectedNewline)?;
         let idx = self.terms.parse_existing_id(&mut self.strings, id);
         let Ok(idx) = idx else {
-            if self.is_version(4, 8, 7) {
-                // Z3 4.8.7 seems to have a bug where it can emit a non-existent term id here.
+            if self.quirk_applies(Quirk::AttachEnodeUnknownTerm) {
+                self.quirks.fire(Quirk::AttachEnodeUnknownTerm);
                 return Ok(());
             } else {
                 return idx.map(|_| ());
@@ -315,7 +425,7 @@ impl Z3LogParser for Z3Parser {
         let iidx = created_by.as_ref().map(|(i, _)| *i);
         let enode = self
             .egraph
-            .new_enode(iidx, idx, z3_generation, &self.stack)?;
+            .new_enode(iidx, idx, z3_generation, self.active_check, &self.stack)?;
         if let Some((_, yields_terms)) = created_by {
             // If `None` then this is a ground term not created by an instantiation.
             yields_terms.try_reserve(1)?;
@@ -406,16 +516,23 @@ impl Z3LogParser for Z3Parser {
         };
 
         let mut blamed = Vec::new();
+        let mut mismatches = EqualityMismatches::default();
         while let Some(word) = l.next() {
             if let Some(first_term) = word.strip_prefix('(') {
                 // assumes that if we see "(#A", the next word in the split is "#B)"
                 let second_term = l.next().ok_or(Error::UnexpectedNewline)?.strip_suffix(')').ok_or(Error::TupleMissingParens)?;
                 let from = self.parse_existing_enode(first_term)?;
                 let to = self.parse_existing_enode(second_term)?;
-                // See comment in `EGraph::get_equalities`
-                let can_mismatch = || self.is_ge_version(4, 12, 3) &&
-                    self.terms[self.egraph.get_owner(to)].kind.app_name().is_some_and(|app| &self.strings[app] == "if");
-                self.egraph.blame_equalities(from, to, &self.stack, &mut blamed, can_mismatch)?;
+                let applies = self.quirk_applies(Quirk::IfTermRootMismatch);
+                let (terms, strings, egraph, quirks) = (&self.terms, &self.strings, &self.egraph, &mut self.quirks);
+                let can_mismatch = || {
+                    let is_if = terms[egraph.get_owner(to)].kind.app_name().is_some_and(|app| &strings[app] == "if");
+                    if applies && is_if {
+                        quirks.fire(Quirk::IfTermRootMismatch);
+                    }
+                    applies && is_if
+                };
+                self.egraph.blame_equalities(from, to, &self.stack, &mut blamed, &mut mismatches, can_mismatch)?;
             } else {
                 let term = self.parse_existing_enode(word)?;
                 blamed.try_reserve(1)?;
@@ -423,8 +540,11 @@ impl Z3LogParser for Z3Parser {
             };
         }
 
-        let match_ = Match { kind, blamed: blamed.into_boxed_slice() };
-        self.insts.new_match(fingerprint, match_)?;
+        let match_ = Match { kind, blamed: blamed.into_boxed_slice(), check: self.active_check };
+        let match_ = self.insts.new_match(fingerprint, match_)?;
+        let mismatches = mismatches.into_vec();
+        self.equality_mismatches.try_reserve(mismatches.len())?;
+        self.equality_mismatches.extend(mismatches.into_iter().map(|mismatch| MatchEqualityMismatch { match_, mismatch }));
         Ok(())
     }
 
@@ -486,7 +606,7 @@ impl Z3LogParser for Z3Parser {
             }
             _ => return Err(Error::UnknownInstMethod(method.to_string())),
         };
-        let match_ = Match { kind, blamed: blamed.into_boxed_slice() };
+        let match_ = Match { kind, blamed: blamed.into_boxed_slice(), check: self.active_check };
         self.insts.new_match(fingerprint, match_)?;
         Ok(())
     }
@@ -510,9 +630,14 @@ impl Z3LogParser for Z3Parser {
             proof_id,
             z3_generation,
             yields_terms: Default::default(),
-            cost: 1.0,
+            check: self.active_check,
+            case_split: self.search.current_case_split(&self.stack),
         };
         let iidx = self.insts.new_inst(fingerprint, inst)?;
+        if let Some(quant) = self.insts[match_].kind.quant_idx() {
+            self.quantifiers[quant].instances.try_reserve(1)?;
+            self.quantifiers[quant].instances.push(iidx);
+        }
         self.inst_stack.try_reserve(1)?;
         self.inst_stack.push((iidx, Vec::new()));
         Ok(())
@@ -524,10 +649,60 @@ impl Z3LogParser for Z3Parser {
         Self::expect_completed(l)
     }
 
+    fn decide_and_or<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let term = l.next().ok_or(Error::UnexpectedNewline)?;
+        let term = self.terms.parse_existing_id(&mut self.strings, term)?;
+        let branch = l.next().ok_or(Error::UnexpectedNewline)?;
+        let branch = self.terms.parse_existing_id(&mut self.strings, branch)?;
+        Self::expect_completed(l)?;
+        self.search.new_case_split(term, branch, self.active_check, &self.stack)
+    }
+
+    fn decide<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let lit = self.expect_literal(&mut l)?;
+        Self::expect_completed(l)?;
+        self.search.new_decision(lit, self.active_check, &self.stack)
+    }
+
+    fn assign<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let lit = self.expect_literal(&mut l)?;
+        let mut kind = l.next().ok_or(Error::UnexpectedNewline)?;
+        let is_decision = kind == "decision";
+        if is_decision {
+            kind = l.next().ok_or(Error::UnexpectedNewline)?;
+        }
+        let kind = JustificationKind::parse(kind);
+        let lits = self.gobble_literals(l)?;
+        self.search.new_assignment(lit, is_decision, Justification { kind, lits }, &self.stack)
+    }
+
+    fn conflict<'a>(&mut self, l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let lits = self.gobble_literals(l)?;
+        self.search.new_conflict(lits, self.active_check, &self.stack)
+    }
+
+    fn resolve_lit<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let level = l.next().ok_or(Error::UnexpectedNewline)?;
+        let level = level.parse::<u32>().map_err(Error::InvalidResolveLevel)?;
+        let lit = self.expect_literal(&mut l)?;
+        Self::expect_completed(l)?;
+        self.search.resolve(ResolveStep::Lit { level, lit })
+    }
+
+    fn resolve_process<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let lit = self.expect_literal(&mut l)?;
+        Self::expect_completed(l)?;
+        self.search.resolve(ResolveStep::Process(lit))
+    }
+
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+
     fn eof(&mut self) {
         self.terms.end_of_file();
-        // TODO: this shouldn't be done here.
-        self.compute_costs();
+        self.proofs.link_insts(&self.insts.insts);
+        self.rewrites = Rewrites::compute(self);
     }
 
     fn push<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
@@ -535,7 +710,7 @@ impl Z3LogParser for Z3Parser {
         let scope = scope.parse::<usize>().map_err(Error::InvalidFrameInteger)?;
         // Return if there is unexpectedly more data
         Self::expect_completed(l)?;
-        self.stack.new_frame(scope)
+        self.stack.new_frame(scope, &mut self.quirks)
     }
 
     fn pop<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
@@ -545,30 +720,39 @@ impl Z3LogParser for Z3Parser {
         let scope = scope.parse::<usize>().map_err(Error::InvalidFrameInteger)?;
         // Return if there is unexpectedly more data
         Self::expect_completed(l)?;
-        self.stack.pop_frames(num, scope)
+        self.stack.pop_frames(num, scope, &mut self.quirks)
+    }
+
+    fn begin_check<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let scope = l.next().ok_or(Error::UnexpectedNewline)?;
+        let scope = scope.parse::<usize>().map_err(Error::InvalidFrameInteger)?;
+        // Return if there is unexpectedly more data
+        Self::expect_completed(l)?;
+        self.checks.raw.try_reserve(1)?;
+        let check = self.checks.push_and_get_key(Check { scope, result: None });
+        self.active_check = Some(check);
+        Ok(())
+    }
+
+    fn query_done<'a>(&mut self, mut l: impl Iterator<Item = &'a str>) -> Result<()> {
+        let result = l.next().map(|r| CheckResult::parse(r).ok_or_else(|| Error::UnknownCheckResult(r.to_string()))).transpose()?;
+        // Return if there is unexpectedly more data
+        Self::expect_completed(l)?;
+        if let Some(check) = self.active_check.take() {
+            self.checks[check].result = result;
+        }
+        Ok(())
     }
 }
 
 impl Z3Parser {
-    fn compute_costs(&mut self) {
-        let mut insts = self.insts.insts.as_mut_slice();
-        while let Some((last, others)) = insts.split_last_mut() {
-            insts = others;
-            let match_ = &self.insts.matches[last.match_];
-            let deps: Vec<_> = match_
-                .due_to_enodes()
-                .filter_map(|(_, blame)| self.egraph[blame].created_by)
-                .collect();
-            let num_deps = deps.len() as f32;
-            for blamed in deps {
-                let cost = last.cost / num_deps;
-                let blamed_inst = &mut insts[blamed];
-                blamed_inst.cost += cost;
-                if let Some(qidx) = match_.kind.quant_idx() {
-                    self.quantifiers[qidx].cost += cost;
-                }
-            }
-        }
+    /// The enodes whose equality lookups would have resolved differently if
+    /// equalities from popped stack frames were not ignored, once for each
+    /// match which blamed them. These are not parse errors, non-empty results
+    /// point to places where older versions of this parser built wrong
+    /// equality edges.
+    pub fn equality_mismatches(&self) -> &[MatchEqualityMismatch] {
+        &self.equality_mismatches
     }
 
     pub fn meaning(&self, tidx: TermIdx) -> Option<&Meaning> {
@@ -578,6 +762,341 @@ impl Z3Parser {
     pub fn quant_count_incl_theory_solving(&self) -> (usize, bool) {
         (self.quantifiers.len(), self.insts.has_theory_solving_inst())
     }
+    pub fn quantifiers(&self) -> &TiVec<QuantIdx, Quantifier> {
+        &self.quantifiers
+    }
+    /// The name (`:qid`) of a quantifier as it appears in the log.
+    pub fn quant_name(&self, quant: QuantIdx) -> String {
+        match &self.quantifiers[quant].kind {
+            QuantKind::Other(name) | QuantKind::NamedQuant(name) => self.strings[*name].to_string(),
+            QuantKind::Lambda => "<null>".to_string(),
+            QuantKind::UnnamedQuant { name, id } => format!("{}!{id}", &self.strings[*name]),
+        }
+    }
+    /// All matches in the order they were found, including those which were
+    /// never instantiated.
+    pub fn matches(&self) -> &TiVec<MatchIdx, Match> {
+        &self.insts.matches
+    }
+    /// All instantiations in the order they happened.
+    pub fn insts(&self) -> &TiVec<InstIdx, Instantiation> {
+        &self.insts.insts
+    }
+
+    /// All checks in the order they were started.
+    pub fn checks(&self) -> &TiVec<CheckIdx, Check> {
+        &self.checks
+    }
+    /// The cost of each instantiation parsed so far under the given model,
+    /// e.g. [`CostKind::default()`](super::cost::CostKind).
+    pub fn inst_costs(&self, model: &(impl CostModel + ?Sized)) -> Costs {
+        model.costs(self)
+    }
+    /// Statistics about what happened during each check, with the given
+    /// instantiation [`costs`](Self::inst_costs).
+    pub fn check_stats(&self, costs: &Costs) -> TiVec<CheckIdx, CheckStats> {
+        let mut stats: TiVec<CheckIdx, CheckStats> = self.checks.iter().map(|_| CheckStats::default()).collect();
+        let mut quants: TiVec<CheckIdx, FxHashSet<QuantIdx>> = self.checks.iter().map(|_| FxHashSet::default()).collect();
+        for match_ in self.insts.matches.iter() {
+            if let Some(check) = match_.check {
+                stats[check].matches += 1;
+            }
+        }
+        for (idx, inst) in self.insts.insts.iter_enumerated() {
+            let Some(check) = inst.check else {
+                continue;
+            };
+            stats[check].insts += 1;
+            stats[check].cost += costs[idx];
+            if let Some(quant) = self.insts[inst.match_].kind.quant_idx() {
+                quants[check].insert(quant);
+            }
+        }
+        for enode in self.egraph.enodes.iter() {
+            if let Some(check) = enode.check {
+                stats[check].enodes += 1;
+            }
+        }
+        for (stats, quants) in stats.iter_mut().zip(quants) {
+            stats.quantifiers = quants.len();
+        }
+        stats
+    }
+    /// The instantiation of `match_`, `None` if it was never instantiated.
+    pub fn match_inst(&self, match_: MatchIdx) -> Option<InstIdx> {
+        self.insts.match_inst(match_)
+    }
+    /// The matches which were never instantiated, a high number of these
+    /// for a quantifier suggests that its patterns are too permissive.
+    pub fn unused_matches(&self) -> impl Iterator<Item = MatchIdx> + '_ {
+        self.insts.unused_matches()
+    }
+    /// Statistics about the matches and instantiations of each quantifier,
+    /// with the given instantiation [`costs`](Self::inst_costs).
+    pub fn quant_stats(&self, costs: &Costs) -> TiVec<QuantIdx, QuantStats> {
+        let mut stats: TiVec<QuantIdx, QuantStats> = self
+            .quantifiers
+            .iter()
+            .map(|quant| {
+                // The children of a quantifier term are its patterns followed
+                // by the body.
+                let children = quant.term.map(|term| &*self[term].child_ids).unwrap_or_default();
+                let patterns = children.split_last().map(|(_, patterns)| patterns).unwrap_or_default();
+                let patterns = patterns.iter().map(|&pattern| PatternStats { pattern, matches: 0, insts: 0 }).collect();
+                QuantStats { patterns, ..Default::default() }
+            })
+            .collect();
+        let mut generations: TiVec<QuantIdx, (u64, usize)> = self.quantifiers.iter().map(|_| (0, 0)).collect();
+        // The quantifiers of all instantiations each instantiation depends
+        // on, directly or indirectly.
+        let mut ancestor_quants: TiVec<InstIdx, RoaringBitmap> = TiVec::with_capacity(self.insts.insts.len());
+        for (idx, inst) in self.insts.insts.iter_enumerated() {
+            let mut ancestors = RoaringBitmap::new();
+            for parent in parents(self, inst) {
+                ancestors |= &ancestor_quants[parent];
+                if let Some(quant) = self[self[parent].match_].kind.quant_idx() {
+                    ancestors.insert(usize::from(quant) as u32);
+                }
+            }
+            let kind = &self[inst.match_].kind;
+            if let Some(quant) = kind.quant_idx() {
+                let stats = &mut stats[quant];
+                stats.insts += 1;
+                if let Some(generation) = inst.z3_generation {
+                    stats.max_generation = stats.max_generation.max(Some(generation));
+                    generations[quant].0 += generation as u64;
+                    generations[quant].1 += 1;
+                }
+                stats.yields += inst.yields_terms.len();
+                stats.cost += costs[idx];
+                if !ancestors.contains(usize::from(quant) as u32) {
+                    stats.self_cost += costs[idx];
+                }
+            }
+            ancestor_quants.push(ancestors);
+        }
+        for (idx, match_) in self.insts.matches.iter_enumerated() {
+            let Some(quant) = match_.kind.quant_idx() else {
+                continue;
+            };
+            let stats = &mut stats[quant];
+            let instantiated = self.insts.match_inst(idx).is_some();
+            stats.matches += 1;
+            stats.unused_matches += usize::from(!instantiated);
+            if let Some(pattern) = match_.kind.pattern() {
+                let idx = stats.patterns.iter().position(|p| p.pattern == pattern).unwrap_or_else(|| {
+                    stats.patterns.push(PatternStats { pattern, matches: 0, insts: 0 });
+                    stats.patterns.len() - 1
+                });
+                stats.patterns[idx].matches += 1;
+                stats.patterns[idx].insts += usize::from(instantiated);
+            }
+        }
+        for (stats, (sum, count)) in stats.iter_mut().zip(generations) {
+            stats.mean_generation = (count > 0).then(|| sum as f32 / count as f32);
+        }
+        stats
+    }
+}
+
+impl Z3Parser {
+    /// The CDCL search: decisions, assignments and conflicts.
+    pub fn search(&self) -> &SearchTrace {
+        &self.search
+    }
+    /// The quantifier instantiations which produced literals involved in
+    /// the given conflict.
+    pub fn conflict_insts(&self, conflict: ConflictIdx) -> Vec<InstIdx> {
+        self.search.conflict_insts(conflict, |e| self.egraph[e].created_by)
+    }
+    /// The quantifier instantiations which produced literals involved in
+    /// conflicts, with the number of conflicts each was involved in, most
+    /// first.
+    pub fn insts_in_conflicts(&self) -> Vec<(InstIdx, usize)> {
+        self.search.insts_in_conflicts(|e| self.egraph[e].created_by)
+    }
+
+    /// The proof DAG, empty unless the log was produced with proofs enabled.
+    pub fn proofs(&self) -> &Proofs {
+        &self.proofs
+    }
+    /// The final step of the refutation: the last proof step which concludes
+    /// `false`.
+    pub fn proof_root(&self) -> Option<ProofIdx> {
+        let is_false = |t: TermIdx| {
+            let term = &self.terms[t];
+            term.child_ids.is_empty()
+                && matches!(term.kind, TermKind::ProofOrApp(ProofOrApp { is_proof: false, name }) if &self.strings[name] == "false")
+        };
+        let steps = self.proofs.steps();
+        steps.iter_enumerated().rev().find(|(_, s)| s.conclusion.is_some_and(is_false)).map(|(idx, _)| idx)
+    }
+    /// The instantiations used by the final refutation, `None` if the log
+    /// contains no proof of `false`. All other instantiations were not needed
+    /// to prove the query unsat.
+    pub fn insts_in_proof(&self) -> Option<FxHashSet<InstIdx>> {
+        let root = self.proof_root()?;
+        let steps = self.proofs.steps();
+        Some(self.proofs.reachable(root).into_iter().filter_map(|s| steps[s].inst).collect())
+    }
+
+    /// For every instantiation, whether it was (transitively) used by the
+    /// final refutation: either its `quant-inst` step is part of the proof,
+    /// or it produced a term which a used instantiation was triggered by.
+    /// `None` if the log contains no proof of `false`.
+    pub fn inst_usefulness(&self) -> Option<TiVec<InstIdx, bool>> {
+        let in_proof = self.insts_in_proof()?;
+        let mut useful: TiVec<InstIdx, bool> = self.insts.insts.keys().map(|i| in_proof.contains(&i)).collect();
+        // Instantiations only depend on earlier ones, so walking backwards
+        // visits each instantiation after everything which depends on it.
+        for (idx, inst) in self.insts.insts.iter_enumerated().rev() {
+            if !useful[idx] {
+                continue;
+            }
+            for (_, blame) in self.insts[inst.match_].due_to_enodes() {
+                if let Some(created_by) = self.egraph[blame].created_by {
+                    useful[created_by] = true;
+                }
+            }
+        }
+        Some(useful)
+    }
+    /// The number of useful and wasted instantiations of each quantifier,
+    /// see [`inst_usefulness`](Self::inst_usefulness).
+    pub fn quant_usefulness(&self) -> Option<TiVec<QuantIdx, QuantUsefulness>> {
+        let useful = self.inst_usefulness()?;
+        let mut quants: TiVec<QuantIdx, QuantUsefulness> = self.quantifiers.iter().map(|_| QuantUsefulness::default()).collect();
+        for (inst, useful) in self.insts.insts.iter().zip(useful) {
+            let Some(quant) = self.insts[inst.match_].kind.quant_idx() else {
+                continue;
+            };
+            if useful {
+                quants[quant].useful += 1;
+            } else {
+                quants[quant].wasted += 1;
+            }
+        }
+        Some(quants)
+    }
+
+    /// The instantiations and their cost attributed to each case split
+    /// branch, with the given instantiation [`costs`](Self::inst_costs).
+    pub fn case_split_stats(&self, costs: &Costs) -> TiVec<CaseSplitIdx, BranchStats> {
+        let case_splits = self.search.case_splits();
+        let mut stats: TiVec<CaseSplitIdx, BranchStats> = case_splits.iter().map(|_| BranchStats::default()).collect();
+        for (idx, inst) in self.insts.insts.iter_enumerated() {
+            if let Some(case_split) = inst.case_split {
+                stats[case_split].insts += 1;
+                stats[case_split].cost += costs[idx];
+            }
+        }
+        for stats in stats.iter_mut() {
+            stats.total_insts = stats.insts;
+            stats.total_cost = stats.cost;
+        }
+        // Children always come after their parent, so walking backwards
+        // sums up each subtree before it is added to its parent.
+        for (idx, case_split) in case_splits.iter_enumerated().rev() {
+            if let Some(parent) = case_split.parent {
+                let (insts, cost) = (stats[idx].total_insts, stats[idx].total_cost);
+                stats[parent].total_insts += insts;
+                stats[parent].total_cost += cost;
+            }
+        }
+        stats
+    }
+    /// The `n` case split branches with the highest cost, including the
+    /// cost of the branches nested within them, most expensive first.
+    pub fn most_expensive_branches(&self, n: usize, costs: &Costs) -> Vec<(CaseSplitIdx, BranchStats)> {
+        let mut stats: Vec<_> = self.case_split_stats(costs).into_iter_enumerated().collect();
+        stats.sort_by(|a, b| b.1.total_cost.total_cmp(&a.1.total_cost).then(a.0.cmp(&b.0)));
+        stats.truncate(n);
+        stats
+    }
+}
+
+/// How many instantiations of a quantifier were used by the final proof, see
+/// [`Z3Parser::quant_usefulness`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuantUsefulness {
+    pub useful: usize,
+    pub wasted: usize,
+}
+
+/// The instantiations of a single case split branch, see
+/// [`Z3Parser::case_split_stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BranchStats {
+    /// The instantiations made directly in this branch.
+    pub insts: usize,
+    pub cost: f32,
+    /// Including the instantiations of all nested branches.
+    pub total_insts: usize,
+    pub total_cost: f32,
+}
+
+/// What happened to the matches of a single quantifier, see
+/// [`Z3Parser::quant_stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuantStats {
+    /// All matches found, whether instantiated or not.
+    pub matches: usize,
+    pub insts: usize,
+    /// The matches which were never instantiated.
+    pub unused_matches: usize,
+    /// The matches of each pattern, in the order of the patterns of the
+    /// quantifier. MBQI matches have no pattern.
+    pub patterns: Vec<PatternStats>,
+    pub max_generation: Option<u32>,
+    /// The mean over instantiations with a known generation.
+    pub mean_generation: Option<f32>,
+    /// The enodes created by the instantiations.
+    pub yields: usize,
+    /// The summed cost of all instantiations.
+    pub cost: f32,
+    /// The summed cost of the instantiations which do not depend (directly
+    /// or indirectly) on another instantiation of the same quantifier. Unlike
+    /// [`cost`](Self::cost) this counts the cost of a matching loop once,
+    /// rather than once per iteration.
+    pub self_cost: f32,
+}
+
+impl QuantStats {
+    /// The fraction of matches which were instantiated, `None` if there were
+    /// no matches.
+    pub fn inst_ratio(&self) -> Option<f32> {
+        (self.matches > 0).then(|| self.insts as f32 / self.matches as f32)
+    }
+}
+
+/// The matches of a single pattern of a quantifier, see [`QuantStats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternStats {
+    pub pattern: TermIdx,
+    pub matches: usize,
+    /// The matches which were instantiated.
+    pub insts: usize,
+}
+
+impl PatternStats {
+    /// The fraction of matches which were instantiated, `None` if there were
+    /// no matches.
+    pub fn inst_ratio(&self) -> Option<f32> {
+        (self.matches > 0).then(|| self.insts as f32 / self.matches as f32)
+    }
+}
+
+/// Counts of what happened during a single check, see
+/// [`Z3Parser::check_stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckStats {
+    pub matches: usize,
+    pub insts: usize,
+    pub enodes: usize,
+    /// The number of distinct quantifiers instantiated.
+    pub quantifiers: usize,
+    /// The summed cost of all instantiations.
+    pub cost: f32,
 }
 
 impl std::ops::Index<TermIdx> for Z3Parser {
@@ -598,3 +1117,15 @@ impl std::ops::Index<ENodeIdx> for Z3Parser {
         &self.egraph[idx]
     }
 }
+impl std::ops::Index<InstIdx> for Z3Parser {
+    type Output = Instantiation;
+    fn index(&self, idx: InstIdx) -> &Self::Output {
+        &self.insts[idx]
+    }
+}
+impl std::ops::Index<MatchIdx> for Z3Parser {
+    type Output = Match;
+    fn index(&self, idx: MatchIdx) -> &Self::Output {
+        &self.insts[idx]
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+use smt_log_parser::{
+    items::QuantIdx,
+    parsers::z3::budget::{Budget, Exceeded, QuantBudget},
+};
+
+mod common;
+
+fn budget(quantifiers: &[(&str, QuantBudget)], default: Option<QuantBudget>) -> Budget {
+    let quantifiers: BTreeMap<_, _> = quantifiers.iter().map(|(name, limits)| (name.to_string(), *limits)).collect();
+    Budget { quantifiers, default }
+}
+
+#[test]
+fn prefix_match() {
+    let (parser, mut graph) = common::parse_graph(include_str!("fixtures/budget.log"));
+    let limits = QuantBudget { insts: Some(2), max_generation: Some(3), loop_length: Some(2), ..Default::default() };
+    let violations = budget(&[("loop", limits)], None).check(&parser, &mut graph);
+    let exceeded: Vec<_> = violations.iter().map(|v| v.exceeded).collect();
+    assert_eq!(
+        exceeded,
+        [Exceeded::Insts { limit: 2, actual: 3 }, Exceeded::LoopLength { limit: 2, actual: 3 }]
+    );
+    assert!(violations.iter().all(|v| v.name == "loop!7" && v.budget.as_deref() == Some("loop")));
+
+    // An exact match takes precedence over the prefix.
+    let lenient = QuantBudget { insts: Some(3), loop_length: Some(3), ..Default::default() };
+    let budget = budget(&[("loop", limits), ("loop!7", lenient)], None);
+    assert_eq!(budget.limits_for(&parser, QuantIdx::from(0)), Some((Some("loop!7"), &lenient)));
+    assert!(budget.check(&parser, &mut graph).is_empty());
+}
+
+#[test]
+fn default_limits() {
+    let (parser, mut graph) = common::parse_graph(include_str!("fixtures/cost_models.log"));
+    let no_limits = budget(&[], None);
+    assert!(no_limits.check(&parser, &mut graph).is_empty());
+
+    let limits = QuantBudget { cost: Some(4.0), ..Default::default() };
+    let violations = budget(&[("join", QuantBudget::default())], Some(limits)).check(&parser, &mut graph);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].name, "split");
+    assert_eq!(violations[0].budget, None);
+    assert_eq!(violations[0].exceeded, Exceeded::Cost { limit: 4.0, actual: 8.0 });
+    assert_eq!(violations[0].to_string(), "split (default budget): a cost of 8.0 exceeds the limit of 4.0");
+}
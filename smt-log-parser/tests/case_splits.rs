@@ -0,0 +1,39 @@
+use smt_log_parser::{items::{CaseSplitIdx, InstIdx}, parsers::z3::cost::CostKind, LogParser, Z3Parser};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/case_splits.log");
+
+#[test]
+fn case_split_tree() {
+    let parser = common::parse(LOG);
+    let case_splits = parser.search().case_splits();
+    assert_eq!(case_splits.len(), 3);
+    let (b0, b1, b2) = (CaseSplitIdx::from(0), CaseSplitIdx::from(1), CaseSplitIdx::from(2));
+    // The second split on the same disjunction is made after the first was
+    // backtracked, so both are children of the outer split.
+    assert_eq!(case_splits[b0].parent, None);
+    assert_eq!(case_splits[b0].children, [b1, b2]);
+    assert_eq!(case_splits[b1].term, case_splits[b2].term);
+    assert_ne!(case_splits[b1].branch, case_splits[b2].branch);
+
+    let insts: Vec<_> = (0..3).map(|i| parser[InstIdx::from(i)].case_split).collect();
+    assert_eq!(insts, [Some(b0), Some(b1), Some(b1)]);
+}
+
+#[test]
+fn expensive_branches() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    let costs = parser.inst_costs(&CostKind::default());
+    let stats = parser.case_split_stats(&costs);
+    let (b0, b1, b2) = (CaseSplitIdx::from(0), CaseSplitIdx::from(1), CaseSplitIdx::from(2));
+    assert_eq!((stats[b0].insts, stats[b0].total_insts), (1, 3));
+    assert_eq!((stats[b1].insts, stats[b1].total_insts), (2, 2));
+    assert_eq!(stats[b2].total_insts, 0);
+    // The first instantiation is blamed for the two in the nested branch.
+    assert_eq!(stats[b0].cost, 3.0);
+    assert_eq!(stats[b0].total_cost, 6.0);
+
+    let top: Vec<_> = parser.most_expensive_branches(2, &costs).into_iter().map(|(b, _)| b).collect();
+    assert_eq!(top, [b0, b1]);
+}
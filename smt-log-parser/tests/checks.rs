@@ -0,0 +1,29 @@
+use smt_log_parser::{items::{CheckIdx, CheckResult}, parsers::z3::{cost::CostKind, inst_graph::InstGraph}};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/checks.log");
+
+#[test]
+fn segment_checks() {
+    let parser = common::parse(LOG);
+    let checks = parser.checks();
+    assert_eq!(checks.len(), 2);
+    let first = CheckIdx::from(0);
+    let second = CheckIdx::from(1);
+    assert_eq!((checks[first].scope, checks[first].result), (0, Some(CheckResult::Unknown)));
+    assert_eq!((checks[second].scope, checks[second].result), (1, Some(CheckResult::Unsat)));
+
+    let stats = parser.check_stats(&parser.inst_costs(&CostKind::default()));
+    assert_eq!((stats[first].matches, stats[first].insts, stats[first].enodes), (1, 1, 3));
+    assert_eq!((stats[second].matches, stats[second].insts, stats[second].enodes), (2, 2, 6));
+    assert_eq!(stats[first].quantifiers, 1);
+    // Each instantiation is blamed for the (single) following one.
+    assert_eq!(stats[first].cost + stats[second].cost, 6.0);
+
+    let mut graph = InstGraph::from(&parser);
+    graph.retain_check(second);
+    let visible = graph.retain_visible_nodes_and_reconnect();
+    assert_eq!(visible.node_count, 2);
+    assert!(graph.visible_graph.node_weights().all(|n| n.check == Some(second)));
+}
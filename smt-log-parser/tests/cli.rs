@@ -0,0 +1,221 @@
+use std::process::Command;
+
+use serde_json::Value;
+
+fn run(args: &[&str]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser")).arg("--json").args(args).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+const COST_MODELS: &str = "tests/fixtures/cost_models.log";
+
+#[test]
+fn stats() {
+    let stats = run(&["stats", "tests/fixtures/quant_stats.log"]);
+    let stats = &stats[0];
+    assert_eq!(stats["outcome"], "completed");
+    assert_eq!(stats["quantifiers"], 2);
+    assert_eq!(stats["matches"], 6);
+    assert_eq!(stats["unused_matches"], 1);
+    assert_eq!(stats["insts"], 5);
+}
+
+#[test]
+fn quantifiers() {
+    let report = run(&["quantifiers", "--sort-by", "name", COST_MODELS]);
+    let names: Vec<_> = report["results"].as_array().unwrap().iter().map(|q| q["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["join", "split"]);
+    let report = run(&["quantifiers", "--cost-model", "inclusive-subtree", "-n", "1", COST_MODELS]);
+    let results = report["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["name"], "split");
+}
+
+#[test]
+fn top() {
+    let report = run(&["top", "--by", "branching", "-n", "2", COST_MODELS]);
+    let children: Vec<_> = report["results"].as_array().unwrap().iter().map(|i| i["children"].as_u64().unwrap()).collect();
+    assert_eq!(children, [2, 1]);
+}
+
+#[test]
+fn loops() {
+    let report = run(&["loops", "tests/fixtures/matching_loop.log"]);
+    assert_eq!(report["results"]["found"], 1);
+    assert_eq!(report["results"]["loops"][0]["insts"].as_array().unwrap().len(), 3);
+}
+
+fn read_json(path: &std::path::Path) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn batch_json() {
+    let output_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("batch_json");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    let output = output_dir.to_str().unwrap();
+    let report = run(&["batch", "tests/fixtures/batch/settings.json", "-o", output]);
+    assert_eq!(report["outcome"], "completed");
+    assert_eq!(report["results"]["reused"], false);
+    assert_eq!(report["results"]["written"].as_array().unwrap().len(), 5);
+    let insts = read_json(&output_dir.join("instantiations.json"));
+    let children: Vec<_> = insts.as_array().unwrap().iter().map(|i| i["children"].as_u64().unwrap()).collect();
+    assert_eq!(children, [2, 1]);
+    assert_eq!(read_json(&output_dir.join("quantifiers.json")).as_array().unwrap().len(), 2);
+
+    // The second run loads the snapshot written by the first.
+    let report = run(&["batch", "tests/fixtures/batch/settings.json", "-o", output]);
+    assert_eq!(report["results"]["reused"], true);
+    assert_eq!(read_json(&output_dir.join("instantiations.json")), insts);
+}
+
+#[test]
+fn batch_reuses_matching_snapshot() {
+    let output_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("batch_reuse");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::create_dir_all(&output_dir).unwrap();
+    let log = std::fs::canonicalize(COST_MODELS).unwrap();
+    let settings = output_dir.join("settings.json");
+    let run_with = |line_limit: usize| {
+        let json = serde_json::json!({ "file": log, "reuses": true, "line_limit": line_limit });
+        std::fs::write(&settings, json.to_string()).unwrap();
+        run(&["batch", settings.to_str().unwrap(), "-o", output_dir.join("out").to_str().unwrap()])
+    };
+    let report = run_with(10);
+    assert_eq!((&report["outcome"], &report["results"]["reused"]), (&"line-limit".into(), &false.into()));
+    // The saved outcome is reported rather than that of loading the snapshot.
+    let report = run_with(10);
+    assert_eq!((&report["outcome"], &report["results"]["reused"]), (&"line-limit".into(), &true.into()));
+    // Different limits parse the log again.
+    let report = run_with(0);
+    assert_eq!((&report["outcome"], &report["results"]["reused"]), (&"completed".into(), &false.into()));
+}
+
+#[test]
+fn batch_txt() {
+    let output_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("batch_txt");
+    let report = run(&["batch", "tests/fixtures/batch/settings.txt", "-o", output_dir.to_str().unwrap()]);
+    assert_eq!(report["outcome"], "line-limit");
+    assert_eq!(report["results"]["written"].as_array().unwrap().len(), 2);
+    let summary = read_json(&output_dir.join("summary.json"));
+    assert_eq!(summary["quantifiers"], 1);
+}
+
+#[test]
+fn check() {
+    let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .args(["--json", "check", "tests/fixtures/budget.log", "--budget", "tests/fixtures/budget.json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let exceeded: Vec<_> =
+        report["results"]["violations"].as_array().unwrap().iter().map(|v| v["exceeded"].as_str().unwrap()).collect();
+    assert_eq!(exceeded, ["insts", "loop_length"]);
+
+    let report = run(&["check", COST_MODELS, "--budget", "tests/fixtures/budget.json"]);
+    assert_eq!(report["results"]["checked"], 0);
+
+    // Stopping before the end of the log fails the check even without
+    // violations.
+    let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .args(["check", COST_MODELS, "--budget", "tests/fixtures/budget.json", "--timeout", "0"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("the budget was not checked"));
+}
+
+#[test]
+fn check_partial_snapshot() {
+    use smt_log_parser::{LogParser, Z3Parser};
+
+    let log = std::fs::read_to_string(COST_MODELS).unwrap();
+    let mut parser = Z3Parser::from_str(&log);
+    parser.process_until(|_, state| state.lines_read < 10);
+    let parser = parser.take_parser();
+    let snapshot = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("check_partial_snapshot.snap");
+    parser.save_snapshot_file(None, false, &snapshot).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .args(["check", snapshot.to_str().unwrap(), "--budget", "tests/fixtures/budget.json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("when it was snapshotted"));
+}
+
+#[test]
+fn diff() {
+    let report = run(&["diff", "tests/fixtures/diff_old.log", "tests/fixtures/diff_new.log"]);
+    let statuses: Vec<_> =
+        report["quantifiers"].as_array().unwrap().iter().map(|q| q["status"].as_str().unwrap()).collect();
+    assert_eq!(statuses, ["changed", "added", "removed"]);
+    assert_eq!(report["quantifiers"][0]["delta"]["insts"], 2);
+}
+
+#[test]
+fn export_graph() {
+    let output_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let output = output_dir.join("export_graph.json");
+    let report = run(&["export", COST_MODELS, "--format", "json", "-o", output.to_str().unwrap()]);
+    assert_eq!(report["results"]["nodes"], 5);
+    let graph = read_json(&output);
+    assert_eq!(graph["edges"].as_array().unwrap().len(), 4);
+    assert_eq!(graph["nodes"][3]["quantifier"], "join");
+    // Cost models are named as on the command line.
+    assert_eq!(graph["cost_model"], "split-share");
+
+    let output = output_dir.join("export_graph.graphml");
+    let log = "tests/fixtures/matching_loop.log";
+    let report = run(&["export", log, "--format", "graphml", "--matching-loops", "-o", output.to_str().unwrap()]);
+    assert_eq!(report["results"]["nodes"], 3);
+    assert!(std::fs::read_to_string(&output).unwrap().starts_with("<?xml"));
+
+    // Snapshots contain the parsed log, not a graph with costs.
+    let output = output_dir.join("export_graph.snap");
+    let _ = std::fs::remove_file(&output);
+    let rejected = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .args(["export", COST_MODELS, "--cost-model", "inclusive-subtree", "-o", output.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(rejected.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("only apply to the graph formats"));
+    assert!(!output.exists());
+}
+
+#[test]
+fn export_partial() {
+    let output = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("export_partial.json");
+    let exported = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .args(["--json", "export", COST_MODELS, "--format", "json", "--timeout", "0", "-o", output.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(exported.status.success());
+    let report: Value = serde_json::from_slice(&exported.stdout).unwrap();
+    assert_eq!(report["results"]["outcome"], "timeout");
+    assert!(String::from_utf8_lossy(&exported.stderr).contains("only part of it was exported"));
+
+    let output = output.with_extension("snap");
+    let _ = std::fs::remove_file(&output);
+    let rejected = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+        .args(["export", COST_MODELS, "--timeout", "0", "-o", output.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(rejected.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("only exported of completely parsed logs"));
+    assert!(!output.exists());
+}
+
+#[test]
+fn invalid_timeout() {
+    for timeout in ["-1", "nan", "inf", "1e20"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_smt-log-parser"))
+            .args(["stats", COST_MODELS, "--timeout", timeout])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{timeout}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("invalid value"), "{timeout}");
+    }
+}
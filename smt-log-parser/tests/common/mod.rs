@@ -0,0 +1,29 @@
+//! Setup shared by the integration tests, included with `mod common;`.
+#![allow(dead_code)]
+
+use smt_log_parser::{parsers::z3::inst_graph::InstGraph, Cvc5Parser, DiagnosticPolicy, LogParser, ParserOptions, Z3Parser};
+
+/// Options which fail parsing on the first error.
+pub fn strict() -> ParserOptions {
+    ParserOptions { diagnostics: DiagnosticPolicy::AbortAfter(0) }
+}
+
+/// Parse a whole Z3 log, failing on the first error.
+pub fn parse(log: &str) -> Z3Parser {
+    Z3Parser::from_str(log).with_options(strict()).process_all().unwrap()
+}
+
+pub fn parse_with(log: &str, diagnostics: DiagnosticPolicy) -> Z3Parser {
+    Z3Parser::from_str(log).with_options(ParserOptions { diagnostics }).process_all().unwrap()
+}
+
+/// Parse a whole Z3 log and build its instantiation graph.
+pub fn parse_graph(log: &str) -> (Z3Parser, InstGraph) {
+    let parser = parse(log);
+    let graph = InstGraph::from(&parser);
+    (parser, graph)
+}
+
+pub fn parse_cvc5(log: &str, diagnostics: DiagnosticPolicy) -> Z3Parser {
+    Cvc5Parser::from_str(log).with_options(ParserOptions { diagnostics }).process_all().unwrap().into_model()
+}
@@ -0,0 +1,65 @@
+use petgraph::graph::NodeIndex;
+use smt_log_parser::{
+    items::{InstIdx, QuantIdx},
+    parsers::z3::{
+        cost::{CostKind, CostModel},
+        inst_graph::InstGraph,
+    },
+};
+
+mod common;
+
+/// Instantiation 0 yields the terms matched by 1 and 2, which in turn yield
+/// the two terms of the multi-pattern matched by 3. Instantiation 4 is
+/// unrelated but has a high generation.
+const LOG: &str = include_str!("fixtures/cost_models.log");
+
+#[test]
+fn builtin_models() {
+    let parser = common::parse(LOG);
+    let expected = [
+        (CostKind::SplitShare, [4.0, 1.5, 1.5, 1.0, 1.0]),
+        (CostKind::InclusiveSubtree, [4.0, 2.0, 2.0, 1.0, 1.0]),
+        (CostKind::ExclusiveSubtree, [4.0, 1.0, 1.0, 1.0, 1.0]),
+        (CostKind::YieldsWeighted, [8.0, 2.5, 2.5, 1.0, 1.0]),
+        (CostKind::GenerationWeighted, [12.0, 5.0, 5.0, 4.0, 11.0]),
+    ];
+    for (kind, expected) in expected {
+        assert_eq!(CostKind::from_name(kind.name()), Some(kind));
+        let costs = parser.inst_costs(&kind);
+        let costs: Vec<_> = costs.iter().map(|(_, cost)| cost).collect();
+        assert_eq!(costs, expected, "{kind}");
+    }
+
+    let name = |q: QuantIdx| parser[q].kind.name().map(|name| &parser.strings[name]);
+    let of_quants = |kind: CostKind| {
+        let quants = parser.inst_costs(&kind).of_quants(&parser);
+        quants.iter_enumerated().map(|(q, cost)| (name(q), *cost)).collect::<Vec<_>>()
+    };
+    // Only the shares passed on by 1, 2 and 3 count, 0 and 4 depend on
+    // nothing.
+    assert_eq!(of_quants(CostKind::SplitShare), [(Some("split"), 3.0), (Some("join"), 1.0)]);
+    assert_eq!(of_quants(CostKind::InclusiveSubtree), [(Some("split"), 9.0), (Some("join"), 1.0)]);
+}
+
+#[test]
+fn switching_model_reranks() {
+    let parser = common::parse(LOG);
+    let visible = |graph: &mut InstGraph| {
+        graph.retain_visible_nodes_and_reconnect();
+        let mut insts: Vec<_> = graph.visible_graph.node_weights().map(|n| n.inst_idx).collect();
+        insts.sort();
+        insts
+    };
+    let mut graph = InstGraph::from(&parser);
+    assert_eq!(graph.cost_model(), CostKind::default());
+    graph.keep_n_most_costly(2);
+    assert_eq!(visible(&mut graph), [InstIdx::from(0), InstIdx::from(1)]);
+
+    graph.reset_visibility_to(true);
+    graph.set_cost_model(&parser, CostKind::GenerationWeighted);
+    let last = &graph.orig_graph[NodeIndex::from(InstIdx::from(4))];
+    assert_eq!((last.cost(), last.cost_rank()), (11.0, 1));
+    graph.keep_n_most_costly(2);
+    assert_eq!(visible(&mut graph), [InstIdx::from(0), InstIdx::from(4)]);
+}
@@ -0,0 +1,73 @@
+use smt_log_parser::{
+    items::{BlameKind, CheckIdx, CheckResult, InstIdx, MatchKind, QuantIdx, QuantKind},
+    parsers::z3::{cost::CostKind, inst_graph::InstGraph},
+    DiagnosticPolicy,
+};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/cvc5_inst.log");
+const ERRORS: &str = include_str!("fixtures/cvc5_errors.log");
+
+#[test]
+fn instantiations() {
+    let parser = common::parse_cvc5(LOG, DiagnosticPolicy::AbortAfter(0));
+    assert_eq!(parser.quant_count_incl_theory_solving(), (2, false));
+    let step = &parser[QuantIdx::from(0)];
+    assert!(matches!(step.kind, QuantKind::NamedQuant(name) if &parser.strings[name] == "step"));
+    assert_eq!(step.num_vars, 1);
+    assert_eq!(parser[QuantIdx::from(1)].num_vars, 2);
+
+    let results: Vec<_> = parser.checks().iter().map(|c| c.result).collect();
+    assert_eq!(results, [Some(CheckResult::Unsat), Some(CheckResult::Sat)]);
+    let insts: Vec<_> = (0..4).map(|i| &parser[InstIdx::from(i)]).collect();
+    let checks: Vec<_> = insts.iter().map(|i| i.check.unwrap()).collect();
+    let (c0, c1) = (CheckIdx::from(0), CheckIdx::from(1));
+    assert_eq!(checks, [c0, c0, c0, c1]);
+
+    // The quantifier printed only by its name in the second check is the same
+    // one, and without a source its single pattern is used.
+    let kinds: Vec<_> = insts.iter().map(|i| &parser[i.match_].kind).collect();
+    assert!(matches!(kinds[0], MatchKind::Quantifier { .. }));
+    assert!(matches!(kinds[1], MatchKind::Quantifier { .. }));
+    assert!(matches!(kinds[2], MatchKind::MBQI { .. }));
+    assert!(matches!(kinds[3], MatchKind::Quantifier { .. }));
+    assert_eq!(kinds[3].quant_idx(), Some(QuantIdx::from(0)));
+}
+
+#[test]
+fn dependencies() {
+    let parser = common::parse_cvc5(LOG, DiagnosticPolicy::AbortAfter(0));
+    // `P(f(a))` is yielded by the first instantiation and triggers the second,
+    // which yields `P(f(f(a)))` for the last one.
+    let blamed_creators: Vec<Vec<_>> = (0..4)
+        .map(|i| {
+            parser[parser[InstIdx::from(i)].match_]
+                .blamed
+                .iter()
+                .map(|b| match b {
+                    BlameKind::Term { term } => parser[*term].created_by,
+                    _ => unreachable!(),
+                })
+                .collect()
+        })
+        .collect();
+    assert_eq!(blamed_creators, [vec![None], vec![Some(InstIdx::from(0))], vec![], vec![Some(InstIdx::from(1))]]);
+    let costs = parser.inst_costs(&CostKind::default());
+    let costs: Vec<_> = (0..4).map(|i| costs[InstIdx::from(i)]).collect();
+    assert_eq!(costs, [3.0, 2.0, 1.0, 1.0]);
+
+    let graph = InstGraph::from(&parser);
+    assert_eq!(graph.orig_graph.node_count(), 4);
+    assert_eq!(graph.orig_graph.edge_count(), 2);
+}
+
+#[test]
+fn errors() {
+    let parser = common::parse_cvc5(ERRORS, DiagnosticPolicy::Collect);
+    let kinds: Vec<_> = parser.diagnostics().collected().iter().map(|d| (d.line_no, d.error.kind())).collect();
+    assert_eq!(
+        kinds,
+        [(1, "UnexpectedInstantiation"), (4, "InstArityMismatch"), (5, "UnexpectedEnd"), (7, "UnknownLine")]
+    );
+}
@@ -0,0 +1,39 @@
+use smt_log_parser::parsers::z3::diff::{DiffStatus, LogDiff, QuantDelta};
+
+mod common;
+
+#[test]
+fn aligned_quantifiers() {
+    let (old, mut old_graph) = common::parse_graph(include_str!("fixtures/diff_old.log"));
+    let (new, mut new_graph) = common::parse_graph(include_str!("fixtures/diff_new.log"));
+    let diff = LogDiff::new(&old, &mut old_graph, &new, &mut new_graph);
+    let summary: Vec<_> = diff
+        .quantifiers
+        .iter()
+        .map(|diff| (diff.old.as_ref().map(|s| s.name.as_str()), diff.new.as_ref().map(|s| s.name.as_str()), diff.status()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            // Unnamed quantifiers are aligned despite their ids changing.
+            (Some("k!3"), Some("k!8"), DiffStatus::Changed),
+            (None, Some("fresh"), DiffStatus::Added),
+            (Some("removed"), None, DiffStatus::Removed),
+            // Aligned by body after the names did not match.
+            (Some("gone"), Some("renamed"), DiffStatus::Unchanged),
+        ]
+    );
+    let loop_diff = &diff.quantifiers[0];
+    assert_eq!(loop_diff.delta, QuantDelta { insts: 2, cost: 5.0, matching_loops: 1, max_depth: 2 });
+    assert_eq!(loop_diff.new.as_ref().unwrap().matching_loops, 1);
+    assert_eq!(diff.with_status(DiffStatus::Added).count(), 1);
+}
+
+#[test]
+fn identical_logs() {
+    let (old, mut old_graph) = common::parse_graph(include_str!("fixtures/diff_old.log"));
+    let (new, mut new_graph) = common::parse_graph(include_str!("fixtures/diff_old.log"));
+    let diff = LogDiff::new(&old, &mut old_graph, &new, &mut new_graph);
+    assert_eq!(diff.quantifiers.len(), 3);
+    assert!(diff.quantifiers.iter().all(|diff| diff.status() == DiffStatus::Unchanged));
+}
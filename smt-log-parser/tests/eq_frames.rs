@@ -0,0 +1,28 @@
+use smt_log_parser::items::TermIdx;
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/eq_frames.log");
+
+#[test]
+fn popped_equalities_are_ignored() {
+    // Using the popped `a = b` would give `a` a different root than `c` and
+    // fail to explain the blamed equality `(#8 #10)`. Parsed strictly, as a
+    // mismatch is not a parse error.
+    let parser = common::parse(LOG);
+    assert_eq!(parser.matches().len(), 1);
+    assert!(parser.diagnostics().is_empty());
+    let mismatches = parser.equality_mismatches();
+    assert_eq!(mismatches.len(), 1);
+    let mismatch = mismatches[0].mismatch;
+    let (a, b, c) = (TermIdx::from(7), TermIdx::from(8), TermIdx::from(9));
+    assert_eq!(parser[mismatch.enode].owner, a);
+    assert_eq!(parser[mismatch.latest].owner, b);
+    assert_eq!(mismatch.active.map(|e| parser[e].owner), Some(c));
+}
+
+#[test]
+fn no_mismatches_without_push() {
+    let parser = common::parse(include_str!("fixtures/matching_loop.log"));
+    assert!(parser.equality_mismatches().is_empty());
+}
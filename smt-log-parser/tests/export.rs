@@ -0,0 +1,49 @@
+use smt_log_parser::{
+    items::InstIdx,
+    parsers::z3::export::{ExportedBlame, ExportedEdgeKind, ExportedGraph},
+};
+
+mod common;
+
+#[test]
+fn orig_graph() {
+    let (parser, graph) = common::parse_graph(include_str!("fixtures/cost_models.log"));
+    let exported = ExportedGraph::orig(&parser, &graph);
+    assert_eq!(exported.nodes.len(), 5);
+    let join = &exported.nodes[3];
+    assert_eq!(join.quantifier.as_deref(), Some("join"));
+    assert_eq!((join.min_depth, join.max_depth), (Some(2), 2));
+    assert_eq!(join.generation, Some(3));
+    assert_eq!(join.fingerprint, "0x4");
+
+    let edges: Vec<_> = exported.edges.iter().map(|edge| (edge.source, edge.target)).collect();
+    assert_eq!(edges, [(0, 1), (0, 2), (1, 3), (2, 3)]);
+    assert!(exported
+        .edges
+        .iter()
+        .all(|edge| edge.kind == ExportedEdgeKind::Direct && edge.blame == Some(ExportedBlame::Term)));
+
+    let mut graphml = Vec::new();
+    exported.write_graphml(&mut graphml).unwrap();
+    let graphml = String::from_utf8(graphml).unwrap();
+    assert!(graphml.contains(r#"<edge id="e2" source="i1" target="i3">"#));
+    assert!(graphml.contains(r#"<data key="fingerprint">0x4</data>"#));
+    let mut gexf = Vec::new();
+    exported.write_gexf(&mut gexf).unwrap();
+    let gexf = String::from_utf8(gexf).unwrap();
+    assert!(gexf.contains(r#"<node id="i3" label="join">"#));
+    assert_eq!(gexf.matches("<edge ").count(), 4);
+}
+
+#[test]
+fn visible_graph() {
+    let (parser, mut graph) = common::parse_graph(include_str!("fixtures/cost_models.log"));
+    let hidden = [InstIdx::from(1), InstIdx::from(2)];
+    graph.retain_nodes(|node| !hidden.contains(&node.inst_idx));
+    graph.retain_visible_nodes_and_reconnect();
+    let exported = ExportedGraph::visible(&parser, &graph);
+    let nodes: Vec<_> = exported.nodes.iter().map(|node| node.inst).collect();
+    assert_eq!(nodes, [0, 3, 4]);
+    let edges: Vec<_> = exported.edges.iter().map(|edge| (edge.source, edge.target, edge.kind, edge.blame)).collect();
+    assert_eq!(edges, [(0, 3, ExportedEdgeKind::Indirect, None)]);
+}
@@ -0,0 +1,100 @@
+use std::{fs::{File, OpenOptions}, io::Write, path::PathBuf};
+
+use smt_log_parser::{items::TermIdx, parsers::{z3::inst_graph::InstGraph, FileRead, IntoStreamParser, ParseState, StreamParser}, LogParser, Z3Parser};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/matching_loop.log");
+
+/// A log file which is still being written, as by a running solver.
+struct Writer(PathBuf, File);
+impl Writer {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("smt-log-parser-{name}-{}.log", std::process::id()));
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(&path).unwrap();
+        Self(path, file)
+    }
+    fn follow(&self) -> StreamParser<'static, Z3Parser> {
+        let options = common::strict();
+        let (_, reader) = self.0.clone().read_open().unwrap();
+        reader.into_parser().with_options(options).follow()
+    }
+    fn write(&mut self, data: impl AsRef<[u8]>) {
+        self.1.write_all(data.as_ref()).unwrap();
+        self.1.flush().unwrap();
+    }
+}
+impl Drop for Writer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn follow_partial_lines() {
+    let full = Z3Parser::from_str(LOG).process_all().unwrap();
+    let full = InstGraph::from(&full);
+
+    let mut writer = Writer::new("partial");
+    let mut parser = writer.follow();
+    assert!(matches!(parser.process_available(), ParseState::Waiting(_)));
+
+    // Write in chunks which split lines, any line parsed early would be an
+    // error.
+    let mut last = (0, 0);
+    for chunk in LOG.as_bytes().chunks(37) {
+        writer.write(chunk);
+        let ParseState::Waiting(state) = parser.process_available() else {
+            panic!("stopped following early");
+        };
+        let p = parser.parser();
+        assert!(p.diagnostics().is_empty());
+        let insts = InstGraph::from(p).orig_graph.node_count();
+        assert!(last <= (state.lines_read, insts));
+        last = (state.lines_read, insts);
+        assert!(!parser.is_done());
+    }
+    // The last line is parsed only once we know that it is complete, here
+    // it is `[eof]` which ends parsing.
+    assert_eq!(last.0, LOG.lines().count() - 1);
+    assert!(matches!(parser.stop_following(), ParseState::Completed { end_of_stream: false }));
+    assert!(parser.is_done());
+
+    let followed = InstGraph::from(parser.parser());
+    assert_eq!(full.orig_graph.node_count(), followed.orig_graph.node_count());
+    assert_eq!(full.orig_graph.edge_count(), followed.orig_graph.edge_count());
+}
+
+#[test]
+fn follow_unterminated_last_line() {
+    let mut writer = Writer::new("unterminated");
+    let mut parser = writer.follow();
+    writer.write("[tool-version] Z3 4.12.2\n[mk-app] #1 tr");
+    assert!(matches!(parser.process_available(), ParseState::Waiting(state) if state.lines_read == 1));
+    writer.write("ue");
+    assert!(matches!(parser.stop_following(), ParseState::Completed { end_of_stream: true }));
+    assert_eq!(parser.reader_state().lines_read, 2);
+    assert!(parser.parser().diagnostics().is_empty());
+    let parser = parser.take_parser();
+    let name = parser[TermIdx::from(0)].kind.app_name().unwrap();
+    assert_eq!(&parser.strings[name], "true");
+}
+
+#[test]
+fn follow_split_character() {
+    let mut writer = Writer::new("split-char");
+    let mut parser = writer.follow();
+    let line = "[mk-app] #1 café\n".as_bytes();
+    // Stop writing in the middle of the two bytes of `é`.
+    let (start, end) = line.split_at(line.len() - 2);
+    writer.write("[tool-version] Z3 4.12.2\n");
+    writer.write(start);
+    assert!(matches!(parser.process_available(), ParseState::Waiting(state) if state.lines_read == 1));
+    writer.write(end);
+    writer.write("[eof]\n");
+    assert!(matches!(parser.stop_following(), ParseState::Completed { end_of_stream: false }));
+    assert!(parser.parser().diagnostics().is_empty());
+    let parser = parser.take_parser();
+    let name = parser[TermIdx::from(0)].kind.app_name().unwrap();
+    assert_eq!(&parser.strings[name], "café");
+}
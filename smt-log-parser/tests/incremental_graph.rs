@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use smt_log_parser::{parsers::{z3::{cost::CostKind, inst_graph::InstGraph}, ParseState}, LogParser, Z3Parser};
+
+mod common;
+
+const LOGS: [&str; 5] = [
+    include_str!("fixtures/matching_loop.log"),
+    include_str!("fixtures/proofs.log"),
+    include_str!("fixtures/checks.log"),
+    include_str!("fixtures/rewrites.log"),
+    include_str!("fixtures/theory_eq.log"),
+];
+
+fn same(a: &InstGraph, b: &InstGraph) -> bool {
+    bincode::serialize(a).unwrap() == bincode::serialize(b).unwrap()
+}
+
+#[test]
+fn extend_matches_from() {
+    let options = common::strict();
+    for kind in CostKind::ALL {
+        for log in LOGS {
+            let mut parser = Z3Parser::from_str(log).with_options(options.clone());
+            let mut graph = InstGraph::default();
+            graph.set_cost_model(parser.parser(), kind);
+            let from = |parser: &Z3Parser| {
+                let mut graph = InstGraph::from(parser);
+                graph.set_cost_model(parser, kind);
+                graph
+            };
+            let mut grown = 0;
+            loop {
+                let state = parser.process_for(Duration::ZERO);
+                let prev = graph.orig_graph.node_count();
+                graph.extend(parser.parser());
+                grown += usize::from(graph.orig_graph.node_count() > prev);
+                assert!(same(&graph, &from(parser.parser())), "{kind}");
+                match state {
+                    ParseState::Paused(_) => (),
+                    ParseState::Completed { .. } => break,
+                    ParseState::Waiting(_) | ParseState::Error(_) => panic!("{state:?}"),
+                }
+            }
+            let full = from(&Z3Parser::from_str(log).process_all().unwrap());
+            assert!(same(&graph, &full), "{kind}");
+            assert!(grown > 1 || graph.orig_graph.node_count() <= 1);
+        }
+    }
+}
+
+#[test]
+fn extend_keeps_filters_working() {
+    let log = LOGS[0];
+    let half = log.len() / 2;
+    let cut = half + log[half..].find("\n[instance]").unwrap() + 1;
+    let mut graph = InstGraph::from(&Z3Parser::from_str(&log[..cut]).process_all().unwrap());
+    let partial = graph.orig_graph.node_count();
+    graph.keep_n_most_costly(1);
+    graph.retain_visible_nodes_and_reconnect();
+    assert_eq!(graph.visible_graph.node_count(), 1);
+
+    // Extending keeps the filtered nodes hidden and shows the new ones, with
+    // depths continuing the chain.
+    let parser = Z3Parser::from_str(log).process_all().unwrap();
+    graph.extend(&parser);
+    let total = graph.orig_graph.node_count();
+    assert!(total > partial);
+    assert_eq!(graph.visible_graph.node_count(), 1 + total - partial);
+    assert_eq!(graph.orig_graph.node_weights().filter(|n| n.visible()).count(), 1 + total - partial);
+    let depths: Vec<_> = graph.orig_graph.node_weights().map(|n| n.min_depth.unwrap()).collect();
+    assert_eq!(depths, (0..total).collect::<Vec<_>>());
+    graph.reset_visibility_to(true);
+    graph.retain_visible_nodes_and_reconnect();
+    assert_eq!(graph.visible_graph.edge_count(), total - 1);
+}
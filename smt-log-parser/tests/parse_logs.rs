@@ -1,7 +1,7 @@
 use std::time::{Duration, Instant};
 use cap::Cap;
 
-use smt_log_parser::{LogParser, Z3Parser};
+use smt_log_parser::{parsers::ParseState, DiagnosticPolicy, LogParser, ParserOptions, Z3Parser};
 
 #[global_allocator]
 static ALLOCATOR: Cap<std::alloc::System> = Cap::new(std::alloc::System, usize::max_value());
@@ -12,7 +12,6 @@ fn parse_all_logs() {
     // Default to limit of 16GiB.
     let mem = mem.unwrap_or(16) * 1024 * 1024 * 1024;
     ALLOCATOR.set_limit(mem).unwrap();
-    std::env::set_var("SLP_TEST_MODE", "true");
 
     let mut all_logs: Vec<_> = std::fs::read_dir("../logs").unwrap().map(|r| r.unwrap()).collect();
     all_logs.sort_by_key(|dir| dir.path());
@@ -20,7 +19,8 @@ fn parse_all_logs() {
         // Put things in a thread to isolate memory usage more than the default.
         let t = std::thread::spawn(move || {
             let filename = log.path();
-            let (metadata, mut parser) = Z3Parser::from_file(&filename).unwrap();
+            let (metadata, parser) = Z3Parser::from_file(&filename).unwrap();
+            let mut parser = parser.with_options(ParserOptions { diagnostics: DiagnosticPolicy::AbortAfter(0) });
             let file_size = metadata.len();
             let file_size_kb = file_size / 1024;
 
@@ -37,7 +37,7 @@ fn parse_all_logs() {
             let max_mem = start_mem + 2 * file_size + 1024 * 1024 * 1024;
             let now = Instant::now();
 
-            parser.process_check_every(Duration::from_millis(100), |_, _| {
+            let state = parser.process_check_every(Duration::from_millis(100), |_, _| {
                 assert!(now.elapsed() < timeout, "Parsing took longer than timeout");
                 let physical_mem = memory_stats::memory_stats().unwrap().physical_mem as u64;
                 assert!(
@@ -49,6 +49,10 @@ fn parse_all_logs() {
                 );
                 true
             });
+            if let ParseState::Error(err) = state {
+                let diagnostics = parser.parser().diagnostics();
+                panic!("Parsing failed with {err:?}: {:?}", diagnostics.collected());
+            }
             let elapsed = now.elapsed();
             println!("Finished parsing in {elapsed:?} ({} kB/ms)", file_size_kb as u128 / elapsed.as_millis());
             println!();
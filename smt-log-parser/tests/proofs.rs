@@ -0,0 +1,61 @@
+use smt_log_parser::{items::{InstIdx, ProofIdx, ProofRule}, parsers::z3::inst_graph::InstGraph, LogParser, Z3Parser};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/proofs.log");
+
+#[test]
+fn proof_dag() {
+    let parser = common::parse(LOG);
+    let steps = parser.proofs().steps();
+    let rules: Vec<_> = steps.iter().map(|s| s.rule).collect();
+    assert_eq!(rules, [
+        ProofRule::Asserted,
+        ProofRule::QuantInst,
+        ProofRule::QuantInst,
+        ProofRule::QuantInst,
+        ProofRule::ModusPonens,
+        ProofRule::Rewrite,
+        ProofRule::Monotonicity,
+        ProofRule::ModusPonens,
+        ProofRule::ThLemma,
+    ]);
+
+    let mp = &steps[ProofIdx::from(4)];
+    assert_eq!(*mp.premises, [ProofIdx::from(0), ProofIdx::from(1)]);
+    assert!(mp.conclusion.is_some());
+    let monotonicity = &steps[ProofIdx::from(6)];
+    assert_eq!(*monotonicity.premises, [ProofIdx::from(5)]);
+
+    // The instance lines refer to either the proof step or its conclusion.
+    let linked: Vec<_> = steps.iter().filter(|s| s.rule == ProofRule::QuantInst).map(|s| s.inst).collect();
+    assert_eq!(linked, [Some(InstIdx::from(0)), Some(InstIdx::from(1)), Some(InstIdx::from(2))]);
+}
+
+#[test]
+fn used_insts() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    assert_eq!(parser.proof_root(), Some(ProofIdx::from(7)));
+    let used = parser.insts_in_proof().unwrap();
+    // Only the first instantiation is needed to derive `false`.
+    assert_eq!(used.into_iter().collect::<Vec<_>>(), [InstIdx::from(0)]);
+
+    let parser = Z3Parser::from_str(include_str!("fixtures/matching_loop.log")).process_all().unwrap();
+    assert!(parser.proofs().steps().is_empty());
+    assert!(parser.insts_in_proof().is_none());
+}
+
+#[test]
+fn useful_and_wasted() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    let useful = parser.inst_usefulness().unwrap();
+    // The proof only uses the first instantiation, which no other depends on.
+    assert_eq!(useful.raw, [true, false, false]);
+    let quants = parser.quant_usefulness().unwrap();
+    assert_eq!((quants.raw[0].useful, quants.raw[0].wasted), (1, 2));
+
+    let mut graph = InstGraph::from(&parser);
+    graph.retain_useful();
+    let visible: Vec<_> = graph.orig_graph.node_weights().filter(|n| n.visible()).map(|n| n.inst_idx).collect();
+    assert_eq!(visible, [InstIdx::from(0)]);
+}
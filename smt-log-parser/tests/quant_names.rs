@@ -0,0 +1,48 @@
+use smt_log_parser::{items::{QuantIdx, QuantKind}, DiagnosticPolicy};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/quant_names.log");
+
+#[test]
+fn names() {
+    let parser = common::parse(LOG);
+    let named = |idx: usize| match &parser[QuantIdx::from(idx)].kind {
+        QuantKind::NamedQuant(name) => &parser.strings[*name],
+        kind => panic!("{kind:?}"),
+    };
+    // Gobra
+    assert_eq!(named(0), "$Set[ShStruct2[Ref, Ref]]_prog.card_non_negative");
+    // Prusti
+    assert_eq!(named(1), "$Seq[Int]_prog.ranged_seq_index");
+    assert_eq!(named(2), "quant-u-9");
+    assert_eq!(named(3), "prog.l1441-aux");
+    assert_eq!(named(4), "$Snap.___BitVec_64_To$SnapTo___BitVec_64_");
+    // Spaces followed by numbers
+    assert_eq!(named(6), "loop 2 invariant 3");
+    assert_eq!(named(7), "quoted name 2 with spaces");
+    assert_eq!(named(8), "quoted");
+
+    assert!(matches!(parser[QuantIdx::from(5)].kind, QuantKind::UnnamedQuant { id: 12, .. }));
+    assert!(matches!(parser[QuantIdx::from(9)].kind, QuantKind::Lambda));
+    for (idx, patterns) in [(0, 1), (5, 0), (6, 1), (7, 1), (8, 0), (9, 0)] {
+        let quant = &parser[QuantIdx::from(idx)];
+        assert_eq!(quant.num_vars, 1);
+        let term = &parser[quant.term.unwrap()];
+        assert_eq!(term.child_ids.len(), patterns + 1, "{quant:?}");
+    }
+}
+
+#[test]
+fn errors() {
+    let log = "[mk-app] #1 true\n\
+        [mk-var] #2 0\n\
+        [mk-quant] #3 |unterminated 1 #2\n\
+        [mk-quant] #4 no_vars #2\n\
+        [mk-quant] #5 no_body 1\n\
+        [mk-quant] #6 |quoted| #2\n";
+    let parser = common::parse_with(log, DiagnosticPolicy::Collect);
+    let kinds: Vec<_> = parser.diagnostics().collected().iter().map(|d| d.error.kind()).collect();
+    assert_eq!(kinds, ["QuantNameNoBar", "QuantNoNumVars", "UnexpectedNewline", "QuantNoNumVars"]);
+    assert_eq!(parser.quant_count_incl_theory_solving(), (0, false));
+}
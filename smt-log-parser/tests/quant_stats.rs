@@ -0,0 +1,78 @@
+use smt_log_parser::{
+    items::{InstIdx, MatchIdx, QuantIdx, TermIdx},
+    parsers::z3::{cost::CostKind, z3parser::{PatternStats, QuantStats}},
+};
+
+mod common;
+
+#[test]
+fn matching_loop() {
+    let parser = common::parse(include_str!("fixtures/matching_loop.log"));
+    let stats = parser.quant_stats(&parser.inst_costs(&CostKind::SplitShare));
+    let expected = QuantStats {
+        matches: 3,
+        insts: 3,
+        unused_matches: 0,
+        patterns: vec![PatternStats { pattern: TermIdx::from(4), matches: 3, insts: 3 }],
+        max_generation: Some(3),
+        mean_generation: Some(2.0),
+        yields: 9,
+        cost: 6.0,
+        // Only the first iteration of the loop counts.
+        self_cost: 3.0,
+    };
+    assert_eq!(stats.raw, [expected]);
+    let instances = &parser[QuantIdx::from(0)].instances;
+    assert_eq!(*instances, [InstIdx::from(0), InstIdx::from(1), InstIdx::from(2)]);
+}
+
+#[test]
+fn unused_matches_and_patterns() {
+    // The instantiations of `cost_models.log` and a last match which is never
+    // instantiated.
+    let parser = common::parse(include_str!("fixtures/quant_stats.log"));
+    let stats = parser.quant_stats(&parser.inst_costs(&CostKind::SplitShare));
+    let split = QuantStats {
+        matches: 5,
+        insts: 4,
+        unused_matches: 1,
+        patterns: vec![PatternStats { pattern: TermIdx::from(4), matches: 5, insts: 4 }],
+        max_generation: Some(10),
+        mean_generation: Some(3.75),
+        yields: 4,
+        cost: 8.0,
+        self_cost: 5.0,
+    };
+    let join = QuantStats {
+        matches: 1,
+        insts: 1,
+        unused_matches: 0,
+        patterns: vec![PatternStats { pattern: TermIdx::from(10), matches: 1, insts: 1 }],
+        max_generation: Some(3),
+        mean_generation: Some(3.0),
+        yields: 0,
+        cost: 1.0,
+        self_cost: 1.0,
+    };
+    assert_eq!(stats.raw, [split, join]);
+}
+
+#[test]
+fn repeated_fingerprint_keeps_matches() {
+    let parser = common::parse(include_str!("fixtures/unused_matches.log"));
+    // The first match shares its fingerprint with the instantiated second one.
+    let unused: Vec<_> = parser.unused_matches().collect();
+    assert_eq!(unused, [MatchIdx::from(0), MatchIdx::from(2)]);
+    assert_eq!(parser.match_inst(MatchIdx::from(1)), Some(InstIdx::from(0)));
+
+    let stats = &parser.quant_stats(&parser.inst_costs(&CostKind::SplitShare))[QuantIdx::from(0)];
+    assert_eq!((stats.matches, stats.insts, stats.unused_matches), (3, 1, 2));
+    assert_eq!(stats.inst_ratio(), Some(1.0 / 3.0));
+    let patterns = [
+        PatternStats { pattern: TermIdx::from(4), matches: 2, insts: 0 },
+        PatternStats { pattern: TermIdx::from(6), matches: 1, insts: 1 },
+    ];
+    assert_eq!(stats.patterns, patterns);
+    let ratios: Vec<_> = stats.patterns.iter().map(PatternStats::inst_ratio).collect();
+    assert_eq!(ratios, [Some(0.0), Some(1.0)]);
+}
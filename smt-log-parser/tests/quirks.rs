@@ -0,0 +1,57 @@
+use semver::Version;
+use smt_log_parser::{parsers::z3::quirks::Quirk, DiagnosticPolicy};
+
+mod common;
+
+const LOG_4_8_7: &str = include_str!("fixtures/quirks_4_8_7.log");
+const LOG_4_12_2: &str = include_str!("fixtures/quirks_4_12_2.log");
+const LOG_4_12_3: &str = include_str!("fixtures/quirks_4_12_3.log");
+
+#[test]
+fn version_ranges() {
+    let v = |minor, patch| Version::new(4, minor, patch);
+    assert!(Quirk::AttachEnodeUnknownTerm.applies_to(Some(&v(8, 7))));
+    assert!(!Quirk::AttachEnodeUnknownTerm.applies_to(Some(&v(8, 8))));
+    assert!(!Quirk::IfTermRootMismatch.applies_to(Some(&v(12, 2))));
+    assert!(Quirk::IfTermRootMismatch.applies_to(Some(&v(13, 0))));
+    assert!(!Quirk::IfTermRootMismatch.applies_to(None));
+    assert!(Quirk::MissingPop.applies_to(None));
+    assert_eq!(Quirk::MissingAttachEnode.to_string(), "missing-attach-enode (v4.12.2)");
+    assert_eq!(Quirk::IfTermRootMismatch.versions().to_string(), "v4.12.3 and later");
+}
+
+#[test]
+fn fired_in_affected_version() {
+    let parser = common::parse(LOG_4_8_7);
+    let fired: Vec<_> = parser.quirks().fired().collect();
+    assert_eq!(fired, [(Quirk::AttachEnodeUnknownTerm, 1), (Quirk::MissingPop, 1)]);
+
+    let parser = common::parse(LOG_4_12_2);
+    let fired: Vec<_> = parser.quirks().fired().collect();
+    assert_eq!(fired, [(Quirk::MissingAttachEnode, 2)]);
+
+    // The blamed `a = if p a b` has no explanation, so the equality is not
+    // blamed but the match is kept.
+    let parser = common::parse(LOG_4_12_3);
+    let fired: Vec<_> = parser.quirks().fired().collect();
+    assert_eq!(fired, [(Quirk::IfTermRootMismatch, 1)]);
+    assert_eq!(parser.matches().len(), 1);
+}
+
+#[test]
+fn not_applied_to_other_versions() {
+    // Without the workaround the unknown term is an error.
+    let parser = common::parse_with(&LOG_4_8_7.replace("4.8.7", "4.12.1"), DiagnosticPolicy::Collect);
+    assert_eq!(parser.quirks().count(Quirk::AttachEnodeUnknownTerm), 0);
+    assert_eq!(parser.diagnostics().by_kind()[0].kind, "UnknownId");
+    // Leaked frames are worked around regardless of the version.
+    assert_eq!(parser.quirks().count(Quirk::MissingPop), 1);
+
+    let parser = common::parse_with(&LOG_4_12_2.replace("4.12.2", "4.12.1"), DiagnosticPolicy::Collect);
+    assert!(parser.quirks().is_empty());
+    assert_eq!(parser.diagnostics().by_kind()[0].kind, "UnknownEnode");
+
+    let parser = common::parse_with(&LOG_4_12_3.replace("4.12.3", "4.12.2"), DiagnosticPolicy::Collect);
+    assert!(parser.quirks().is_empty());
+    assert_eq!(parser.diagnostics().by_kind()[0].kind, "EnodeRootMismatch");
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use smt_log_parser::{parsers::{z3::inst_graph::InstGraph, ParseState}, LogParser, Z3Parser};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/matching_loop.log");
+
+#[test]
+fn resume_after_timeout() {
+    let options = common::strict();
+    let full = Z3Parser::from_str(LOG).with_options(options.clone()).process_all().unwrap();
+    let full = InstGraph::from(&full);
+
+    // A zero timeout pauses every few lines, resume until done and inspect the
+    // partial state in between.
+    let mut parser = Z3Parser::from_str(LOG).with_options(options);
+    let mut pauses = 0;
+    let mut last_nodes = 0;
+    loop {
+        match parser.process_for(Duration::ZERO) {
+            ParseState::Paused(state) => {
+                pauses += 1;
+                assert_eq!(state, parser.reader_state());
+                let partial = InstGraph::from(parser.parser());
+                assert!(last_nodes <= partial.orig_graph.node_count());
+                last_nodes = partial.orig_graph.node_count();
+            }
+            ParseState::Completed { end_of_stream } => {
+                assert!(!end_of_stream);
+                break;
+            }
+            ParseState::Waiting(_) => unreachable!(),
+            ParseState::Error(err) => panic!("{err:?}"),
+        }
+    }
+    assert!(pauses > 1);
+    assert!(parser.is_done());
+
+    let resumed = InstGraph::from(parser.parser());
+    assert_eq!(full.orig_graph.node_count(), 3);
+    assert_eq!(full.orig_graph.node_count(), resumed.orig_graph.node_count());
+    assert_eq!(full.orig_graph.edge_count(), resumed.orig_graph.edge_count());
+}
@@ -0,0 +1,27 @@
+use smt_log_parser::{items::{InstIdx, TermIdx}};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/rewrites.log");
+
+#[test]
+fn rewrite_chain() {
+    let parser = common::parse(LOG);
+    let (i0, i1, i2) = (InstIdx::from(0), InstIdx::from(1), InstIdx::from(2));
+    // `a + a` is rewritten to `2 * a`, which is rewritten to `g(a)`, which
+    // the quantifier is then instantiated with.
+    let (sum, product, g) = (TermIdx::from(8), TermIdx::from(10), TermIdx::from(12));
+    assert_eq!(parser.rewrites().of_term(sum), [i0]);
+    assert_eq!(parser.rewrites().produced_by(product), Some(i0));
+    assert_eq!(parser.rewrites().consumers(i1), [i2]);
+
+    // The same provenance is found starting from either rewrite.
+    for inst in [i0, i1] {
+        let provenance = parser.rewrite_provenance(inst).unwrap();
+        assert_eq!(provenance.original, sum);
+        assert_eq!(provenance.chain, [i0, i1]);
+        assert_eq!(provenance.results[0], g);
+        assert_eq!(provenance.consumers, [i2]);
+    }
+    assert!(parser.rewrite_provenance(i2).is_none());
+}
@@ -0,0 +1,46 @@
+use smt_log_parser::{items::{ConflictIdx, DecisionIdx, InstIdx, JustificationKind}, LogParser, Z3Parser};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/search.log");
+
+#[test]
+fn decision_tree() {
+    let parser = common::parse(LOG);
+    let search = parser.search();
+    assert_eq!(search.decisions().len(), 3);
+    assert_eq!(search.assignments().len(), 4);
+    assert_eq!(search.conflicts().len(), 1);
+
+    // The third decision backtracks the second one after its frame was popped.
+    let (d0, d1, d2) = (DecisionIdx::from(0), DecisionIdx::from(1), DecisionIdx::from(2));
+    assert_eq!(search.roots().collect::<Vec<_>>(), [d0]);
+    assert_eq!(search.decisions()[d0].children, [d1, d2]);
+    assert_eq!(search.path_to(d2), [d0, d2]);
+    assert_eq!(search.depth(d1), 1);
+    assert!(!search.decisions()[d1].lit.value);
+
+    let conflict = ConflictIdx::from(0);
+    assert_eq!(search.conflicts()[conflict].decision, Some(d1));
+    assert_eq!(search.conflicts()[conflict].steps.len(), 2);
+    assert_eq!(search.subtree_conflicts(d0), [conflict]);
+    assert!(search.subtree_conflicts(d2).is_empty());
+
+    let propagated = &search.assignments().raw[2];
+    assert!(!propagated.is_decision);
+    assert_eq!(propagated.justification.kind, JustificationKind::Clause);
+    assert_eq!(propagated.justification.lits.len(), 2);
+    assert_eq!(propagated.decision, Some(d1));
+}
+
+#[test]
+fn conflict_blame() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    assert!(parser.diagnostics().is_empty());
+    // The conflict literals were produced by the first two instantiations,
+    // the resolved literal by the third.
+    let insts: Vec<_> = (0..3).map(InstIdx::from).collect();
+    assert_eq!(parser.conflict_insts(ConflictIdx::from(0)), insts);
+    let counts: Vec<_> = insts.iter().map(|&i| (i, 1)).collect();
+    assert_eq!(parser.insts_in_conflicts(), counts);
+}
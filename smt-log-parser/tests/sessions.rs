@@ -0,0 +1,40 @@
+use smt_log_parser::{parsers::z3::inst_graph::InstGraph, LogParser, Z3Parser};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/multiple_sessions.log");
+
+#[test]
+fn split_sessions() {
+    let parser = common::parse(LOG);
+    // The second session starts with a `[tool-version]`, the third only
+    // restarts the term ids.
+    assert_eq!(parser.session_count(), 3);
+    assert_eq!(parser.previous_sessions().len(), 2);
+
+    let sessions = parser.into_sessions();
+    let insts: Vec<_> = sessions.iter().map(|s| InstGraph::from(s).orig_graph.node_count()).collect();
+    assert_eq!(insts, [3, 1, 2]);
+    let quants: Vec<_> = sessions.iter().map(|s| s.quant_count_incl_theory_solving().0).collect();
+    assert_eq!(quants, [1, 1, 1]);
+    for session in &sessions {
+        assert_eq!(session.session_count(), 1);
+        assert!(session.version_info().is_some_and(|v| v.solver() == "Z3"));
+    }
+}
+
+#[test]
+fn single_session() {
+    let parser = Z3Parser::from_str(include_str!("fixtures/matching_loop.log")).process_all().unwrap();
+    assert_eq!(parser.session_count(), 1);
+    assert_eq!(parser.into_sessions().len(), 1);
+}
+
+#[test]
+fn reused_first_id() {
+    // Z3 may reuse the id of the first term for a different one within a
+    // run, only recreating the first term itself is a restart.
+    let log = include_str!("fixtures/matching_loop.log").replace("[eof]", "[mk-app] #1 c");
+    let parser = common::parse(&log);
+    assert_eq!(parser.session_count(), 1);
+}
@@ -0,0 +1,50 @@
+use smt_log_parser::{parsers::z3::{inst_graph::InstGraph, snapshot::SNAPSHOT_VERSION}, LogParser, SnapshotError, Z3Parser};
+
+const LOG: &str = include_str!("fixtures/matching_loop.log");
+
+#[test]
+fn snapshot_roundtrip() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    let graph = InstGraph::from(&parser);
+
+    let mut data = Vec::new();
+    parser.save_snapshot(Some(&graph), true, &mut data).unwrap();
+    let snapshot = Z3Parser::load_snapshot(data.as_slice()).unwrap();
+    assert!(snapshot.complete);
+    let (loaded, loaded_graph) = (snapshot.parser, snapshot.graph.unwrap());
+
+    assert_eq!(parser.version_info(), loaded.version_info());
+    assert_eq!(parser.quant_count_incl_theory_solving(), loaded.quant_count_incl_theory_solving());
+    assert_eq!(graph.orig_graph.node_count(), loaded_graph.orig_graph.node_count());
+    assert_eq!(graph.orig_graph.edge_count(), loaded_graph.orig_graph.edge_count());
+    // A graph rebuilt from the loaded parser matches the original one.
+    let rebuilt = InstGraph::from(&loaded);
+    assert_eq!(graph.orig_graph.node_count(), rebuilt.orig_graph.node_count());
+    assert_eq!(graph.orig_graph.edge_count(), rebuilt.orig_graph.edge_count());
+
+    let mut data = Vec::new();
+    parser.save_snapshot(None, false, &mut data).unwrap();
+    let snapshot = Z3Parser::load_snapshot(data.as_slice()).unwrap();
+    assert!(snapshot.graph.is_none());
+    assert!(!snapshot.complete);
+}
+
+#[test]
+fn snapshot_rejects_other_versions() {
+    let parser = Z3Parser::from_str(LOG).process_all().unwrap();
+    let mut data = Vec::new();
+    parser.save_snapshot(None, true, &mut data).unwrap();
+    data[8..12].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+    match Z3Parser::load_snapshot(data.as_slice()) {
+        Err(SnapshotError::VersionMismatch { found, expected }) => {
+            assert_eq!(found, SNAPSHOT_VERSION + 1);
+            assert_eq!(expected, SNAPSHOT_VERSION);
+        }
+        other => panic!("expected version mismatch, got {:?}", other.err()),
+    }
+
+    let result = Z3Parser::load_snapshot(LOG.as_bytes());
+    assert!(matches!(result, Err(SnapshotError::NotASnapshot)));
+    let result = Z3Parser::load_snapshot(&b"SLP"[..]);
+    assert!(matches!(result, Err(SnapshotError::NotASnapshot)));
+}
@@ -0,0 +1,22 @@
+use smt_log_parser::{items::{BlameKind, EqualitySource, InstIdx}, parsers::z3::inst_graph::InstGraph};
+
+mod common;
+
+const LOG: &str = include_str!("fixtures/theory_eq.log");
+
+#[test]
+fn theory_equalities_are_blamed() {
+    let parser = common::parse(LOG);
+    let graph = InstGraph::from(&parser);
+    // The second match only holds because `c = b` by arithmetic, and `b` was
+    // produced by the first instantiation.
+    let edges: Vec<_> = graph.orig_graph.edge_weights().collect();
+    assert_eq!(edges.len(), 1);
+    let BlameKind::TheoryEquality { term, from, to, source: EqualitySource::Theory(theory) } = edges[0] else {
+        panic!("expected a theory equality edge, got {:?}", edges[0]);
+    };
+    assert_eq!(&parser.strings[*theory], "arith");
+    assert_eq!(term, to);
+    assert_eq!(parser[*term].created_by, Some(InstIdx::from(0)));
+    assert_eq!(parser[*from].created_by, None);
+}